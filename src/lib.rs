@@ -5,40 +5,228 @@ use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use lazy_static::lazy_static;
 use nexus::{
     gui::{register_render, render, RenderType},
-    imgui::{InputText, TreeNodeFlags, Ui, Window},
-    keybind::{register_keybind_with_string},
+    imgui::{Condition, InputText, Selectable, StyleVar, TreeNode, TreeNodeFlags, Ui, Window},
+    keybind::{register_keybind_with_string, unregister_keybind},
     log::{self, LogLevel},
     paths::get_addon_dir,
     quick_access::{add_quick_access, remove_quick_access},
     texture::get_texture_or_create_from_file,
+    wnd_proc::{register_wnd_proc, RawWndProc},
     AddonFlags, UpdateProvider,
 };
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
-    ffi::{c_char, CStr},
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    ffi::{c_char, c_void, CStr, OsStr},
     fs,
-    io::Cursor,
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    iter,
+    net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs},
+    os::windows::{ffi::OsStrExt, io::AsRawHandle, process::CommandExt},
     panic,
     path::PathBuf,
-    process::Command,
-    sync::Mutex,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use sysinfo::System;
+use sysinfo::{Pid, System};
 use windows_icons::get_icon_base64_by_path;
+use windows_sys::core::{GUID, HRESULT, PCWSTR, PWSTR};
+use windows_sys::w;
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, LocalFree, BOOL, ERROR_CANCELLED, HANDLE, HWND, LPARAM, SYSTEMTIME, TRUE, WPARAM},
+    Security::Cryptography::{CryptProtectData, CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB},
+    Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW, VS_FIXEDFILEINFO},
+    System::{
+        Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, STGM_READ},
+        Memory::{MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, FILE_MAP_READ, MEMORY_MAPPED_VIEW_ADDRESS},
+        Pipes::PeekNamedPipe,
+        Registry::{RegGetValueW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ},
+        SystemInformation::{GetLocalTime, GetSystemInfo},
+        Threading::{
+            SetPriorityClass, SetProcessAffinityMask, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, CREATE_NO_WINDOW,
+            HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, PROCESS_CREATION_FLAGS,
+        },
+    },
+    UI::{
+        Shell::{DragAcceptFiles, DragFinish, DragQueryFileW, ShellExecuteW, ShellLink, HDROP},
+        WindowsAndMessaging::{
+            EnumWindows, GetWindow, GetWindowThreadProcessId, IsIconic, IsWindowVisible, PostMessageW,
+            SetForegroundWindow, ShowWindow, GW_OWNER, WM_CLOSE, WM_DROPFILES, SHOW_WINDOW_CMD, SW_HIDE, SW_RESTORE, SW_SHOWMAXIMIZED, SW_SHOWMINNOACTIVE,
+            SW_SHOWNORMAL,
+        },
+    },
+};
 
 // --- Configuration & State Management ---
 fn default_true() -> bool {
     true
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum LaunchTrigger {
     OnAddonLoad,
     OnKeybind,
+    OnUnload,
+    // Keybind launches the program if it isn't running and kills it if it is
+    Toggle,
+    // Launches when the character enters the map with this ID (MumbleLink)
+    OnMapEnter(u32),
+    // Closes the program when the character leaves the map with this ID
+    OnMapLeave(u32),
+}
+
+const TRIGGER_OPTIONS: [(LaunchTrigger, &str); 4] = [
+    (LaunchTrigger::OnAddonLoad, "On Addon Start"),
+    (LaunchTrigger::OnKeybind, "On Keybind"),
+    (LaunchTrigger::OnUnload, "On Addon Unload"),
+    (LaunchTrigger::Toggle, "Toggle on Keybind"),
+];
+
+fn map_enter_trigger(program: &ProgramToLaunch) -> Option<u32> {
+    program.triggers.iter().find_map(|trigger| match trigger {
+        LaunchTrigger::OnMapEnter(map_id) => Some(*map_id),
+        _ => None,
+    })
+}
+fn map_leave_trigger(program: &ProgramToLaunch) -> Option<u32> {
+    program.triggers.iter().find_map(|trigger| match trigger {
+        LaunchTrigger::OnMapLeave(map_id) => Some(*map_id),
+        _ => None,
+    })
+}
+
+fn uses_keybind(program: &ProgramToLaunch) -> bool {
+    program.triggers.contains(&LaunchTrigger::OnKeybind) || program.triggers.contains(&LaunchTrigger::Toggle)
+}
+
+// Reads the single `trigger` value written by older versions
+fn deserialize_legacy_trigger<'de, D>(deserializer: D) -> Result<Option<LaunchTrigger>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    LaunchTrigger::deserialize(deserializer).map(Some)
+}
+
+// What a quick access click (or the LAUNCH_ keybind it shares) does for a program
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum QuickAccessAction {
+    #[default]
+    Launch,
+    FocusExisting,
+    Toggle,
+    Restart,
+}
+
+const QUICK_ACCESS_ACTIONS: [(QuickAccessAction, &str); 4] = [
+    (QuickAccessAction::Launch, "Launch"),
+    (QuickAccessAction::FocusExisting, "Focus existing window"),
+    (QuickAccessAction::Toggle, "Toggle (kill if running)"),
+    (QuickAccessAction::Restart, "Restart"),
+];
+
+// What a launch does when the program is already running
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum AlreadyRunningAction {
+    #[default]
+    Ask,
+    FocusExisting,
+    LaunchAnyway,
+    DoNothing,
+}
+
+const ALREADY_RUNNING_ACTIONS: [(AlreadyRunningAction, &str); 4] = [
+    (AlreadyRunningAction::Ask, "Ask"),
+    (AlreadyRunningAction::FocusExisting, "Focus existing window"),
+    (AlreadyRunningAction::LaunchAnyway, "Launch another instance"),
+    (AlreadyRunningAction::DoNothing, "Do nothing"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum WindowState {
+    #[default]
+    Normal,
+    Minimized,
+    Maximized,
+    Hidden,
+}
+
+const WINDOW_STATES: [(WindowState, &str); 4] = [
+    (WindowState::Normal, "Normal"),
+    (WindowState::Minimized, "Minimized"),
+    (WindowState::Maximized, "Maximized"),
+    (WindowState::Hidden, "Hidden"),
+];
+
+// Game mode a program is restricted to, derived from the MumbleLink map type
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum GameMode {
+    #[default]
+    Any,
+    PvE,
+    WvW,
+    PvP,
+}
+
+const GAME_MODES: [(GameMode, &str); 4] = [
+    (GameMode::Any, "Any mode"),
+    (GameMode::PvE, "PvE only"),
+    (GameMode::WvW, "WvW only"),
+    (GameMode::PvP, "PvP only"),
+];
+
+// CPU priority class applied to a launched process
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum ProcessPriority {
+    Idle,
+    BelowNormal,
+    #[default]
+    Normal,
+    AboveNormal,
+    High,
+}
+
+const PROCESS_PRIORITIES: [(ProcessPriority, &str); 5] = [
+    (ProcessPriority::Idle, "Idle"),
+    (ProcessPriority::BelowNormal, "Below normal"),
+    (ProcessPriority::Normal, "Normal"),
+    (ProcessPriority::AboveNormal, "Above normal"),
+    (ProcessPriority::High, "High"),
+];
+
+// Which PowerShell runs .ps1 targets
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum PowerShellHost {
+    #[default]
+    WindowsPowerShell,
+    Pwsh,
+}
+
+const POWERSHELL_HOSTS: [(PowerShellHost, &str); 2] = [
+    (PowerShellHost::WindowsPowerShell, "Windows PowerShell (powershell.exe)"),
+    (PowerShellHost::Pwsh, "PowerShell 7 (pwsh.exe)"),
+];
+
+// File format the profiles are saved in
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+enum ConfigFormat {
+    #[default]
+    Ron,
+    Json,
 }
 
+const CONFIG_FORMATS: [(ConfigFormat, &str); 2] = [
+    (ConfigFormat::Ron, "RON (profiles.ron)"),
+    (ConfigFormat::Json, "JSON (profiles.json)"),
+];
+
+const EXECUTION_POLICIES: [&str; 5] = ["Bypass", "Unrestricted", "RemoteSigned", "AllSigned", "Default"];
+
 // Legacy structure for backwards compatibility
 #[derive(Deserialize)]
 struct LegacyProgramToLaunch {
@@ -59,10 +247,192 @@ struct ProgramToLaunch {
     #[serde(default)]
     display_name: String,
     path: String,
-    trigger: LaunchTrigger,
+    #[serde(default)]
+    triggers: BTreeSet<LaunchTrigger>,
+    // Pre-multi-trigger configs store a single trigger; folded into `triggers` on load
+    #[serde(default, deserialize_with = "deserialize_legacy_trigger", skip_serializing)]
+    trigger: Option<LaunchTrigger>,
     close_on_unload: bool,
     #[serde(default = "default_true")]
     show_in_quick_access: bool,
+    // Kill the process if it hasn't shown a window after this many seconds
+    #[serde(default)]
+    ready_timeout_secs: Option<u64>,
+    // Structured command split out of `path`; empty when `path` couldn't be split unambiguously
+    #[serde(default)]
+    exe_path: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    quick_access_action: QuickAccessAction,
+    // Overrides the default working directory (the executable's folder)
+    #[serde(default)]
+    working_dir: Option<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    run_as_admin: bool,
+    #[serde(default)]
+    window_state: WindowState,
+    // Delay before an OnAddonLoad launch, to spread out startup disk load
+    #[serde(default)]
+    launch_delay_ms: u64,
+    // Ask the program to close via WM_CLOSE before force-killing it
+    #[serde(default = "default_true")]
+    graceful_shutdown: bool,
+    // Also kill everything the program spawned (launchers leave orphans otherwise)
+    #[serde(default)]
+    kill_children: bool,
+    #[serde(default)]
+    on_already_running: AlreadyRunningAction,
+    // Only kill processes this addon launched, not instances the user opened
+    #[serde(default)]
+    only_kill_own_instances: bool,
+    // User-chosen image used for the Quick Access tile instead of the exe's icon
+    #[serde(default)]
+    icon_override: Option<String>,
+    // Disabled programs are kept in the list but never launched or shown in Quick Access
+    #[serde(default = "default_true")]
+    enabled: bool,
+    // Only used to organize the options list, never part of any ID
+    #[serde(default)]
+    group: Option<String>,
+    // Relaunch the program if it exits without us closing it
+    #[serde(default)]
+    keep_alive: bool,
+    // `name` of a program that has to be launched before this one on addon load
+    #[serde(default)]
+    launch_after: Option<String>,
+    // Hold back dependents until this program's process is running
+    #[serde(default)]
+    wait_for_ready: bool,
+    // Only close processes started from this program's exact executable path
+    #[serde(default)]
+    match_full_path: bool,
+    // Default key combination for LAUNCH_{name}. Nexus can't report what the user bound,
+    // so this is the only binding we can carry over when `name` changes.
+    #[serde(default)]
+    keybind: String,
+    // How often force_launch_process ran for this program, shown in the options
+    #[serde(default)]
+    launch_count: u64,
+    // Forward the program's stdout/stderr to the Nexus log
+    #[serde(default)]
+    capture_output: bool,
+    // Extra spawn attempts after a failed launch, e.g. while a scanner has the exe locked
+    #[serde(default)]
+    launch_retries: u32,
+    #[serde(default = "default_retry_delay_ms")]
+    retry_delay_ms: u64,
+    // Launches are skipped outside this mode; OnAddonLoad programs start when it's entered
+    #[serde(default)]
+    active_in: GameMode,
+    // Close the program when the character leaves `active_in`
+    #[serde(default)]
+    close_outside_mode: bool,
+    // Skip the OnAddonLoad launch if the addon is reloaded while the game keeps running
+    #[serde(default)]
+    launch_once_per_session: bool,
+    // Process that has to be running for a launch to happen, e.g. the host of a plugin
+    #[serde(default)]
+    require_process: Option<String>,
+    // Process that prevents the launch while it is running
+    #[serde(default)]
+    skip_if_process: Option<String>,
+    // Server ("host:port" or an http(s) URL) that has to accept a connection before launching
+    #[serde(default)]
+    precheck_url: Option<String>,
+    // File that has to exist for a launch to happen; may use %VARS% and path tokens
+    #[serde(default)]
+    require_file: Option<String>,
+    // Shown instead of the generic message when `require_file` is missing
+    #[serde(default)]
+    require_file_message: Option<String>,
+    // Commands run before launching and after the program was closed by us
+    #[serde(default)]
+    pre_launch: Option<String>,
+    #[serde(default)]
+    post_kill: Option<String>,
+    // Don't launch when the pre-launch command fails or times out
+    #[serde(default)]
+    abort_on_hook_failure: bool,
+    #[serde(default)]
+    priority: ProcessPriority,
+    // Bit N pins the program to logical core N; None leaves it on every core
+    #[serde(default)]
+    cpu_affinity: Option<u64>,
+    // Closes the launched instance once it has run this long
+    #[serde(default)]
+    auto_close_after_secs: Option<u64>,
+    // Indices into `args` that are masked in the UI and stored encrypted
+    #[serde(default)]
+    sensitive_args: HashSet<usize>,
+    // Favorites are kept at the top of the list and of Quick Access
+    #[serde(default)]
+    is_favorite: bool,
+    // Warn once the program's processes use more memory than this
+    #[serde(default)]
+    memory_warn_mb: Option<u64>,
+    // Close the program instead of only warning when it goes over memory_warn_mb
+    #[serde(default)]
+    kill_on_memory_exceed: bool,
+}
+
+impl Default for ProgramToLaunch {
+    fn default() -> Self {
+        ProgramToLaunch {
+            name: String::new(),
+            display_name: String::new(),
+            path: String::new(),
+            triggers: BTreeSet::from([LaunchTrigger::OnAddonLoad]),
+            trigger: None,
+            close_on_unload: false,
+            show_in_quick_access: true,
+            ready_timeout_secs: None,
+            exe_path: String::new(),
+            args: Vec::new(),
+            quick_access_action: QuickAccessAction::Launch,
+            working_dir: None,
+            env: HashMap::new(),
+            run_as_admin: false,
+            window_state: WindowState::Normal,
+            launch_delay_ms: 0,
+            graceful_shutdown: true,
+            kill_children: false,
+            on_already_running: AlreadyRunningAction::Ask,
+            only_kill_own_instances: false,
+            icon_override: None,
+            enabled: true,
+            group: None,
+            keep_alive: false,
+            launch_after: None,
+            wait_for_ready: false,
+            match_full_path: false,
+            keybind: String::new(),
+            launch_count: 0,
+            capture_output: false,
+            launch_retries: 0,
+            retry_delay_ms: default_retry_delay_ms(),
+            active_in: GameMode::Any,
+            close_outside_mode: false,
+            launch_once_per_session: false,
+            require_process: None,
+            skip_if_process: None,
+            precheck_url: None,
+            require_file: None,
+            require_file_message: None,
+            pre_launch: None,
+            post_kill: None,
+            abort_on_hook_failure: false,
+            priority: ProcessPriority::Normal,
+            cpu_affinity: None,
+            auto_close_after_secs: None,
+            sensitive_args: HashSet::new(),
+            is_favorite: false,
+            memory_warn_mb: None,
+            kill_on_memory_exceed: false,
+        }
+    }
 }
 
 // Legacy config for reading old formats
@@ -72,10 +442,238 @@ struct LegacyConfig {
     programs_to_kill: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
+fn default_process_scan_ttl_ms() -> u64 {
+    1000
+}
+fn default_launch_cooldown_ms() -> u64 {
+    1000
+}
+fn default_retry_delay_ms() -> u64 {
+    2000
+}
+fn default_precheck_timeout_ms() -> u64 {
+    1000
+}
+fn default_test_launch_secs() -> u64 {
+    5
+}
+// .ps1 isn't listed, sync_interpreters maps it from the PowerShell settings
+fn default_interpreters() -> HashMap<String, String> {
+    HashMap::from([
+        ("py".to_string(), "python".to_string()),
+        ("js".to_string(), "node".to_string()),
+        ("vbs".to_string(), "cscript //nologo".to_string()),
+    ])
+}
+fn default_execution_policy() -> String {
+    "Bypass".to_string()
+}
+fn default_graceful_shutdown_timeout_ms() -> u64 {
+    3000
+}
+
+const GAME_PROCESS_NAME: &str = "Gw2-64.exe";
+
+fn default_protected_processes() -> Vec<String> {
+    vec![GAME_PROCESS_NAME.to_string()]
+}
+
+// Format version written with every config. 1 is the format from before the field
+// existed, with a single `trigger` per program; 2 keeps triggers only in `triggers`;
+// 3 has exe_path/args split out of the combined path wherever that was possible.
+const CONFIG_VERSION: u32 = 3;
+
+fn default_config_version() -> u32 {
+    1
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 struct Config {
+    #[serde(default = "default_config_version")]
+    version: u32,
     programs_to_launch: Vec<ProgramToLaunch>,
     programs_to_kill: Vec<String>,
+    // How long to wait after WM_CLOSE before force-killing
+    #[serde(default = "default_graceful_shutdown_timeout_ms")]
+    graceful_shutdown_timeout_ms: u64,
+    // Never killed, whichever list or option points at them
+    #[serde(default = "default_protected_processes")]
+    protected_processes: Vec<String>,
+    // How long a scan of running processes is reused before rescanning
+    #[serde(default = "default_process_scan_ttl_ms")]
+    process_scan_ttl_ms: u64,
+    // Kill-list entries that are executable paths, matched against the full path
+    // instead of the process name
+    #[serde(default)]
+    kill_full_path_entries: HashSet<String>,
+    // Answer "No" to the already-running popup after this many seconds, 0 = never
+    #[serde(default)]
+    popup_auto_dismiss_secs: u64,
+    // Master switch: when off nothing is launched, kills still happen
+    #[serde(default = "default_true")]
+    launches_enabled: bool,
+    // Log what launches and kills would do instead of doing it
+    #[serde(default)]
+    dry_run: bool,
+    // Keybind and Quick Access triggers of the same program closer together than this are ignored
+    #[serde(default = "default_launch_cooldown_ms")]
+    launch_cooldown_ms: u64,
+    // Load and "launch all" start at most this many programs per batch, 0 = no limit
+    #[serde(default)]
+    max_concurrent_launches: usize,
+    // Append every launch and kill to history.log in the addon folder
+    #[serde(default)]
+    file_logging: bool,
+    // Queue launches that happen during a loading screen until it is over
+    #[serde(default)]
+    defer_launches_while_loading: bool,
+    // How long the precheck_url connection attempt of a program may take
+    #[serde(default = "default_precheck_timeout_ms")]
+    precheck_timeout_ms: u64,
+    // How long a program started with its Test button runs before it is closed again
+    #[serde(default = "default_test_launch_secs")]
+    test_launch_secs: u64,
+    // File extension (without the dot) -> command that runs such a file, e.g. "py" -> "python"
+    #[serde(default = "default_interpreters")]
+    interpreters: HashMap<String, String>,
+    // Used for .ps1 targets unless "ps1" has its own interpreter entry
+    #[serde(default)]
+    powershell_host: PowerShellHost,
+    #[serde(default = "default_execution_policy")]
+    powershell_execution_policy: String,
+    // Kept the same in every profile, the file holds all of them
+    #[serde(default)]
+    config_format: ConfigFormat,
+}
+
+const DEFAULT_PROFILE: &str = "Default";
+
+// Top-level file holding every profile; CONFIG is a working copy of the active one
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct ProfileStore {
+    active_profile: String,
+    profiles: HashMap<String, Config>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            programs_to_launch: Vec::new(),
+            programs_to_kill: Vec::new(),
+            graceful_shutdown_timeout_ms: default_graceful_shutdown_timeout_ms(),
+            protected_processes: default_protected_processes(),
+            process_scan_ttl_ms: default_process_scan_ttl_ms(),
+            kill_full_path_entries: HashSet::new(),
+            popup_auto_dismiss_secs: 0,
+            launches_enabled: true,
+            dry_run: false,
+            launch_cooldown_ms: default_launch_cooldown_ms(),
+            max_concurrent_launches: 0,
+            file_logging: false,
+            defer_launches_while_loading: false,
+            precheck_timeout_ms: default_precheck_timeout_ms(),
+            test_launch_secs: default_test_launch_secs(),
+            interpreters: default_interpreters(),
+            powershell_host: PowerShellHost::default(),
+            powershell_execution_policy: default_execution_policy(),
+            config_format: ConfigFormat::Ron,
+        }
+    }
+}
+
+// A process name to close, with the options of the program it came from
+#[derive(Clone)]
+struct KillTarget {
+    name: String,
+    graceful: bool,
+    kill_children: bool,
+    // Restricts the kill to these PIDs instead of every process with the name
+    pids: Option<Vec<Pid>>,
+    // Restricts the kill to processes started from this executable
+    exe_path: Option<PathBuf>,
+    // Command run once the matching processes are closed
+    post_kill: Option<String>,
+}
+
+impl KillTarget {
+    // Kill-list entries get a graceful close and leave child processes alone
+    fn from_name(name: &str) -> Self {
+        KillTarget {
+            name: name.to_string(),
+            graceful: true,
+            kill_children: false,
+            pids: None,
+            exe_path: None,
+            post_kill: None,
+        }
+    }
+
+    // Entries marked "match full path" hold an executable path instead of a name
+    fn from_kill_entry(entry: &str, config: &Config) -> Self {
+        if !config.kill_full_path_entries.contains(entry) {
+            return Self::from_name(entry);
+        }
+        let path = PathBuf::from(entry);
+        let name = path
+            .file_name()
+            .map_or_else(|| entry.to_string(), |n| n.to_string_lossy().to_string());
+        KillTarget {
+            exe_path: Some(path),
+            ..Self::from_name(&name)
+        }
+    }
+
+    fn matches(&self, process: &sysinfo::Process) -> bool {
+        let name_matches = if is_wildcard_pattern(&self.name) {
+            wildcard_match(&self.name.to_lowercase(), &process.name().to_lowercase())
+        } else {
+            process.name().eq_ignore_ascii_case(&self.name)
+        };
+        name_matches
+            && self.pids.as_ref().is_none_or(|pids| pids.contains(&process.pid()))
+            && self.exe_path.as_ref().is_none_or(|exe_path| {
+                process.exe().is_some_and(|exe| {
+                    exe.to_string_lossy().eq_ignore_ascii_case(&exe_path.to_string_lossy())
+                })
+            })
+    }
+
+    fn for_program(program: &ProgramToLaunch) -> Option<Self> {
+        let pids = program.only_kill_own_instances.then(|| {
+            LAUNCHED_PIDS
+                .lock()
+                .unwrap()
+                .get(&program.name)
+                .cloned()
+                .unwrap_or_default()
+        });
+        let exe_path = program
+            .match_full_path
+            .then(|| get_program_command(program).map(|(exe_path, _)| PathBuf::from(exe_path)))
+            .flatten();
+        Some(KillTarget {
+            name: get_program_process_name(program)?,
+            graceful: program.graceful_shutdown,
+            kill_children: program.kill_children,
+            pids,
+            exe_path,
+            post_kill: program.post_kill.clone(),
+        })
+    }
+}
+
+// State of the "Pick running process" window; the list is a snapshot taken when it opens
+struct ProcessPicker {
+    filter: String,
+    processes: Vec<(String, u32)>,
+}
+
+// Actions that wipe settings, run only once confirmed in their popup
+#[derive(Clone, Copy)]
+enum DestructiveAction {
+    ResetToDefaults,
+    RemoveAllPrograms,
 }
 
 // Structure to hold pending updates
@@ -90,18 +688,174 @@ enum UpdateAction {
     Remove,
     UpdateDisplayName(String),
     ToggleQuickAccess(bool),
+    Kill,
+    SetIconOverride(Option<String>),
+    RefreshIcon,
+    SetEnabled(bool),
+    Duplicate,
+    Restart,
+    SetPath(String),
+    SetFavorite(bool),
+    TestLaunch,
+    Launch,
+}
+
+// Outcome of the Test button of a program
+#[derive(Clone)]
+enum TestLaunch {
+    Running { until: Instant },
+    NotStarted,
+    ExitedEarly(i32),
+    Finished { pid: u32, seen: bool },
 }
 
 lazy_static! {
     static ref CONFIG: Mutex<Config> = Mutex::new(Config::default());
-    static ref SYSTEM_INFO: Mutex<System> = Mutex::new(System::new_all());
+    static ref PROFILES: Mutex<ProfileStore> = Mutex::new(ProfileStore::default());
+    static ref PROFILE_INPUT: Mutex<String> = Mutex::new(String::with_capacity(64));
+    static ref SYSTEM_INFO: Mutex<ProcessSnapshot> = Mutex::new(ProcessSnapshot {
+        sys: System::new(),
+        refreshed_at: None,
+    });
     static ref ICON_CACHE: Mutex<HashMap<String, PathBuf>> = Mutex::new(HashMap::new());
     static ref LAUNCH_INPUT: Mutex<String> = Mutex::new(String::with_capacity(260));
+    static ref IMPORT_CODE_INPUT: Mutex<String> = Mutex::new(String::new());
     static ref KILL_INPUT: Mutex<String> = Mutex::new(String::with_capacity(64));
-    static ref PENDING_LAUNCH_CONFIRMATION: Mutex<Option<String>> = Mutex::new(None);
+    static ref PROTECTED_INPUT: Mutex<String> = Mutex::new(String::with_capacity(64));
+    static ref SHORTCUT_CACHE: Mutex<HashMap<String, ShortcutTarget>> = Mutex::new(HashMap::new());
+    // Which row's Copy button was clicked last, for the "Copied!" tooltip
+    static ref LAST_COPIED: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+    // When each program was last triggered by keybind or Quick Access, for the launch cooldown
+    static ref LAST_TRIGGERED: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+    static ref MUMBLE_LINK: Mutex<Option<MumbleLinkView>> = Mutex::new(None);
+    static ref LAST_TICK: Mutex<Option<TickSample>> = Mutex::new(None);
+    // Launches held back by a loading screen, run once the game is playable again
+    static ref DEFERRED_LAUNCHES: Mutex<Vec<ProgramToLaunch>> = Mutex::new(Vec::new());
+    // Launches waiting for their batch when max_concurrent_launches is set
+    static ref LAUNCH_QUEUE: Mutex<VecDeque<ProgramToLaunch>> = Mutex::new(VecDeque::new());
+    // Modification time of the profiles file as we last read or wrote it
+    static ref CONFIG_FILE_MTIME: Mutex<Option<SystemTime>> = Mutex::new(None);
+    // Program name -> launches counted since the last save, kept across a hot reload
+    static ref UNSAVED_LAUNCHES: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+    // Mirrors Config::interpreters; command parsing runs while the UI holds CONFIG
+    static ref INTERPRETERS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+    static ref INTERPRETER_INPUT: Mutex<(String, String)> = Mutex::new((String::new(), String::new()));
+    // Programs whose missing required file was already reported this session
+    static ref REQUIRE_FILE_WARNED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+    // Program whose Rebind button is waiting for a key press
+    static ref KEYBIND_CAPTURE: Mutex<Option<String>> = Mutex::new(None);
+    // Exit code of the last child launched per program, None while it is still running
+    static ref EXIT_CODES: Mutex<HashMap<String, Option<i32>>> = Mutex::new(HashMap::new());
+    static ref TEST_LAUNCHES: Mutex<HashMap<String, TestLaunch>> = Mutex::new(HashMap::new());
+    // Why the last launch of a program failed, shown in its row until dismissed or a launch succeeds
+    static ref LAUNCH_ERRORS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+    // Summary line of the last "Validate & Clean"
+    static ref VALIDATION_RESULT: Mutex<Option<String>> = Mutex::new(None);
+    // The last program removed with its "-" button, its index and when, for "Undo remove"
+    static ref REMOVED_PROGRAM: Mutex<Option<(ProgramToLaunch, usize, Instant)>> = Mutex::new(None);
+    // Programs currently over their memory_warn_mb, so each crossing is reported once
+    static ref MEMORY_EXCEEDED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+    // Bumped per program to cancel its pending auto-close timer
+    static ref AUTO_CLOSE_GENERATION: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+    // Serializes writes and rotation of history.log
+    static ref HISTORY_LOG_LOCK: Mutex<()> = Mutex::new(());
+    // Latest launch and kill events, oldest first
+    static ref HISTORY: Mutex<VecDeque<HistoryEvent>> = Mutex::new(VecDeque::new());
+    static ref EXE_INFO_CACHE: Mutex<HashMap<String, ExeInfo>> = Mutex::new(HashMap::new());
+    // Command -> when get_document_path last looked at it and what it found
+    static ref DOCUMENT_PATHS: Mutex<HashMap<String, (Instant, Option<String>)>> = Mutex::new(HashMap::new());
+    // Program name -> names of the other programs launching the same executable
+    static ref DUPLICATE_PATHS: Mutex<HashMap<String, Vec<String>>> = Mutex::new(HashMap::new());
+    static ref LAUNCH_FILTER: Mutex<String> = Mutex::new(String::with_capacity(64));
+    static ref KILL_PREVIEW: Mutex<Option<Vec<String>>> = Mutex::new(None);
+    static ref PROCESS_PICKER: Mutex<Option<ProcessPicker>> = Mutex::new(None);
+    static ref PENDING_DESTRUCTIVE_ACTION: Mutex<Option<DestructiveAction>> = Mutex::new(None);
+    // Files dropped onto the game window, added by the next options frame
+    static ref DROPPED_FILES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    // When the options were last drawn; drops only count while they are open
+    static ref OPTIONS_LAST_RENDERED: Mutex<Option<Instant>> = Mutex::new(None);
+    // Tools found on the first run, with whether they are ticked in the wizard
+    static ref ONBOARDING: Mutex<Option<Vec<(ProgramToLaunch, bool)>>> = Mutex::new(None);
+    static ref ENV_INPUTS: Mutex<HashMap<String, (String, String)>> = Mutex::new(HashMap::new());
+    static ref PATH_INPUTS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+    // The program waiting for a Yes/No and when the question was asked
+    static ref PENDING_LAUNCH_CONFIRMATION: Mutex<Option<(ProgramToLaunch, Instant)>> = Mutex::new(None);
+    static ref DONT_ASK_AGAIN: Mutex<bool> = Mutex::new(false);
+    static ref BACKGROUND_THREADS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+    static ref LAUNCHED_PIDS: Mutex<HashMap<String, Vec<Pid>>> = Mutex::new(HashMap::new());
+    static ref RUNNING_STATUS: Mutex<(Instant, HashMap<String, bool>)> = Mutex::new((Instant::now(), HashMap::new()));
+    static ref KEEP_ALIVE: Mutex<HashMap<String, KeepAliveState>> = Mutex::new(HashMap::new());
+    // Rebuilt with every process scan, so the options UI can show it without scanning
+    static ref PROCESS_USAGE: Mutex<ProcessUsage> = Mutex::new(ProcessUsage::default());
+    // GetSystemInfo's processor count, it doesn't change while the game runs
+    static ref LOGICAL_CORES: u32 = query_logical_core_count();
+}
+
+// Set during unload so background threads stop before the DLL goes away
+static UNLOADING: AtomicBool = AtomicBool::new(false);
+// Mirrors Config::process_scan_ttl_ms so process lookups never need the CONFIG lock,
+// which the UI holds while it asks for running status
+static PROCESS_SCAN_TTL_MS: AtomicU64 = AtomicU64::new(1000);
+// Cleared at the start of unload so the watcher doesn't relaunch what unload closes
+static KEEP_ALIVE_ACTIVE: AtomicBool = AtomicBool::new(false);
+// Mirrors Config::file_logging, history_log is called from places that hold CONFIG
+static FILE_LOGGING: AtomicBool = AtomicBool::new(false);
+// Set while a write of history.ron is scheduled
+static HISTORY_SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+// Set by the config watcher, cleared by the render callback that reloads
+static CONFIG_RELOAD_PENDING: AtomicBool = AtomicBool::new(false);
+// Set while a worker is draining LAUNCH_QUEUE; only changed with the queue locked
+static LAUNCH_QUEUE_ACTIVE: AtomicBool = AtomicBool::new(false);
+// "Include subfolders" option of Add from Folder
+static ADD_FOLDER_RECURSIVE: AtomicBool = AtomicBool::new(false);
+// The game window accepting file drops, as a number since HWND can't be kept in a static
+static DROP_TARGET: AtomicUsize = AtomicUsize::new(0);
+
+struct ProcessSnapshot {
+    sys: System,
+    refreshed_at: Option<Instant>,
+}
+
+// CPU as a share of the whole machine (like Task Manager) and memory in bytes
+#[derive(Default)]
+struct ProcessUsage {
+    // Summed over the PIDs we launched for each program
+    by_program: HashMap<String, (f32, u64)>,
+    // Summed over every process with the same (lowercase) name
+    by_name: HashMap<String, (f32, u64)>,
 }
 
 // --- Helper Functions ---
+fn is_wildcard_pattern(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+// `*` matches any run of characters and `?` exactly one, like file name wildcards
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` swallow one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
 
 // Convert legacy program to new format
 impl From<LegacyProgramToLaunch> for ProgramToLaunch {
@@ -110,9 +864,10 @@ impl From<LegacyProgramToLaunch> for ProgramToLaunch {
             name: String::new(), // Will be set properly below
             display_name: legacy.display_name,
             path: legacy.path.clone(),
-            trigger: legacy.trigger,
+            triggers: BTreeSet::from([legacy.trigger]),
             close_on_unload: legacy.close_on_unload,
             show_in_quick_access: legacy.show_in_quick_access,
+            ..Default::default()
         };
 
         // Fix the name field - remove .exe and sanitize
@@ -145,6 +900,8 @@ impl From<LegacyConfig> for Config {
         let mut new_config = Config {
             programs_to_launch: Vec::new(),
             programs_to_kill: legacy.programs_to_kill,
+            version: default_config_version(),
+            ..Default::default()
         };
 
         let mut used_names = HashSet::new();
@@ -175,45 +932,375 @@ fn sanitize_identifier(text: &str) -> String {
     text.replace(' ', "_")
 }
 
+// Splits user-typed arguments on whitespace, keeping "double quoted" groups together.
+// Backslashes are left alone so Windows paths survive intact. Combined commands are split
+// the same way, so join_arguments turns either back into the same text.
+fn split_arguments(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+    args
+}
+
+fn quote_argument(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(char::is_whitespace) {
+        format!("\"{}\"", arg)
+    } else {
+        arg.to_string()
+    }
+}
+
+fn join_arguments(args: &[String]) -> String {
+    args.iter().map(|arg| quote_argument(arg)).collect::<Vec<_>>().join(" ")
+}
+
+fn to_wide(text: &str) -> Vec<u16> {
+    OsStr::new(text).encode_wide().chain(iter::once(0)).collect()
+}
+
+// Addon-specific placeholders, documented in the options UI
+const PATH_TOKENS: [(&str, &str); 3] = [
+    ("{ADDON_DIR}", "this addon's folder"),
+    ("{GAME_DIR}", "the Guild Wars 2 install folder"),
+    ("{CONFIG_DIR}", "the folder holding the addon's settings"),
+];
+
+fn path_token_value(token: &str) -> Option<PathBuf> {
+    match token {
+        "{ADDON_DIR}" | "{CONFIG_DIR}" => get_addon_dir(env!("CARGO_PKG_NAME")),
+        // The addon is loaded into the game process, so its exe is the game's
+        "{GAME_DIR}" => std::env::current_exe().ok()?.parent().map(Path::to_path_buf),
+        _ => None,
+    }
+}
+
+fn expand_tokens(text: &str) -> String {
+    let mut result = text.to_string();
+    for (token, _) in PATH_TOKENS {
+        if result.contains(token) {
+            if let Some(value) = path_token_value(token) {
+                result = result.replace(token, &value.to_string_lossy());
+            }
+        }
+    }
+    result
+}
+
+// Everything that can appear in a configured path or argument
+fn expand_path_variables(text: &str) -> String {
+    expand_env_vars(&expand_tokens(text))
+}
+
+// Replaces %VAR% with the environment variable's value. Unknown variables are
+// left as they are, so a literal % in a path keeps working.
+fn expand_env_vars(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        let Some(len) = after.find('%') else {
+            break;
+        };
+        let name = &after[..len];
+        result.push_str(&rest[..start]);
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => {
+                result.push_str(&value);
+                rest = &after[len + 1..];
+            }
+            _ => {
+                if !name.is_empty() {
+                    log::log(
+                        LogLevel::Debug,
+                        "SYSTEM",
+                        &format!("Unknown environment variable %{}% left unexpanded", name),
+                    );
+                }
+                // The closing % may start the next variable
+                result.push('%');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// Reads a null-terminated UTF-16 buffer filled in by a Windows API
+fn from_wide(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+const EXECUTABLE_EXTENSIONS: [&str; 5] = ["exe", "com", "bat", "cmd", "ps1"];
+
+// Extensions that are run rather than opened: executables plus scripts with an interpreter
+fn launchable_extensions() -> Vec<String> {
+    let mut extensions: Vec<String> = EXECUTABLE_EXTENSIONS.iter().map(|ext| ext.to_string()).collect();
+    extensions.extend(INTERPRETERS.lock().unwrap().keys().map(|ext| ext.to_ascii_lowercase()));
+    extensions
+}
+
+// Interpreter command split into program and arguments, if `path` is a mapped script
+fn interpreter_for(path: &str) -> Option<Vec<String>> {
+    let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    let interpreters = INTERPRETERS.lock().unwrap();
+    let command = interpreters.iter().find(|(key, _)| key.eq_ignore_ascii_case(&ext))?.1;
+    Some(split_arguments(command)).filter(|parts| !parts.is_empty())
+}
+
+fn powershell_command(host: PowerShellHost, execution_policy: &str) -> String {
+    let exe = match host {
+        PowerShellHost::WindowsPowerShell => "powershell.exe",
+        PowerShellHost::Pwsh => "pwsh.exe",
+    };
+    match execution_policy.trim() {
+        "" => format!("{} -NoProfile -File", exe),
+        policy => format!("{} -NoProfile -ExecutionPolicy {} -File", exe, policy),
+    }
+}
+
+// Program and full argument list for running `script` through `interpreter`
+fn script_invocation(interpreter: &[String], script: &str, args: &[String]) -> (String, Vec<String>) {
+    let mut invocation_args = interpreter[1..].to_vec();
+    invocation_args.push(script.to_string());
+    invocation_args.extend(args.iter().cloned());
+    (interpreter[0].clone(), invocation_args)
+}
+
+fn is_batch_script(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("bat") || ext.eq_ignore_ascii_case("cmd"))
+}
+
+// Raw command line for `cmd.exe`. With /s, cmd strips only the outer pair of quotes, so
+// a quoted script path followed by quoted arguments arrives unchanged. Arguments holding
+// cmd metacharacters are quoted too, or cmd would act on them.
+fn batch_command_line(script: &str, args: &[String]) -> String {
+    let mut line = format!("\"{}\"", script);
+    for arg in args {
+        line.push(' ');
+        if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "&|<>^()".contains(c)) {
+            line.push_str(&format!("\"{}\"", arg));
+        } else {
+            line.push_str(arg);
+        }
+    }
+    format!("/d /s /c \"{}\"", line)
+}
+
+fn sync_interpreters() {
+    let config = CONFIG.lock().unwrap();
+    let mut interpreters = config.interpreters.clone();
+    if !interpreters.keys().any(|ext| ext.eq_ignore_ascii_case("ps1")) {
+        interpreters.insert(
+            "ps1".to_string(),
+            powershell_command(config.powershell_host, &config.powershell_execution_policy),
+        );
+    }
+    *INTERPRETERS.lock().unwrap() = interpreters;
+    DOCUMENT_PATHS.lock().unwrap().clear();
+}
+
+// True for `scheme:rest` targets like https://... or steam://rungameid/...
+// A single-letter scheme is a drive letter, not a URL.
+fn is_url(command_str: &str) -> bool {
+    let command_str = command_str.trim();
+    let Some((scheme, rest)) = command_str.split_once(':') else {
+        return false;
+    };
+    scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !command_str.contains(char::is_whitespace)
+}
+
+// The options UI classifies every row on every frame, so answers are reused for a short
+// while, and dropped when the interpreters (and so the launchable extensions) change
+const DOCUMENT_PATH_TTL: Duration = Duration::from_secs(5);
+
+// Returns the path if the command points at an existing non-executable file
+// (a document, spreadsheet, image...) or a URL, which the shell opens with the
+// associated app or protocol handler
+fn get_document_path(command_str: &str) -> Option<String> {
+    if let Some((checked_at, document)) = DOCUMENT_PATHS.lock().unwrap().get(command_str) {
+        if checked_at.elapsed() < DOCUMENT_PATH_TTL {
+            return document.clone();
+        }
+    }
+    let document = find_document_path(command_str);
+    let mut cache = DOCUMENT_PATHS.lock().unwrap();
+    cache.retain(|_, (checked_at, _)| checked_at.elapsed() < DOCUMENT_PATH_TTL);
+    cache.insert(command_str.to_string(), (Instant::now(), document.clone()));
+    document
+}
+
+fn find_document_path(command_str: &str) -> Option<String> {
+    if is_url(command_str) {
+        return Some(command_str.trim().to_string());
+    }
+    let expanded = expand_path_variables(command_str);
+    let path_str = expanded.trim().trim_matches('"');
+    let path = Path::new(path_str);
+    if !path.is_file() {
+        return None;
+    }
+    // Shortcuts are resolved to their target instead of being opened by the shell
+    let is_executable = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| launchable_extensions().iter().any(|e| ext.eq_ignore_ascii_case(e)) || ext.eq_ignore_ascii_case("lnk"));
+    (!is_executable).then(|| path_str.to_string())
+}
+
 fn get_executable_and_args_from_command(
     command_str: &str,
 ) -> Option<(String, Vec<String>)> {
-    let command_lower = command_str.to_lowercase();
-    
-    let exe_end_index = command_lower.rfind(".exe").map(|i| i + 4)
-        .or_else(|| command_lower.rfind(".com").map(|i| i + 4))
-        .or_else(|| command_lower.rfind(".bat").map(|i| i + 4));
+    if let Some(document) = get_document_path(command_str) {
+        return Some((document, Vec::new()));
+    }
+    let command_str = &expand_path_variables(command_str);
+
+    if is_shortcut(command_str) {
+        return resolve_shortcut(command_str).map(|target| (target.exe_path, target.args));
+    }
+
+    // An unquoted path with spaces and no arguments
+    let command_str = command_str.trim();
+    if Path::new(command_str).is_file() {
+        return Some((command_str.to_string(), Vec::new()));
+    }
+
+    // An unquoted path followed by arguments: the executable ends at the first
+    // extension that completes an existing file. ASCII lowercasing keeps byte offsets valid.
+    let command_lower = command_str.to_ascii_lowercase();
+    let extensions = launchable_extensions();
+    let exe_end_index = command_lower
+        .match_indices('.')
+        .filter_map(|(i, _)| {
+            extensions
+                .iter()
+                .find(|ext| command_lower[i + 1..].starts_with(ext.as_str()))
+                .map(|ext| i + 1 + ext.len())
+        })
+        .find(|&end| {
+            command_str[end..].starts_with(char::is_whitespace) && Path::new(&command_str[..end]).is_file()
+        });
 
     let (exe_path_str, args_str) = match exe_end_index {
-        Some(index) if Path::new(&command_str[..index]).is_file() => {
-            (&command_str[..index], &command_str[index..])
-        }
+        Some(index) => (&command_str[..index], &command_str[index..]),
         _ => {
-            let parts = shell_words::split(command_str).ok()?;
+            let mut parts = split_arguments(command_str);
             if parts.is_empty() { return None; }
-            let (exe, args_vec) = parts.split_first().unwrap();
-            let args = args_vec.join(" ");
-            return Some((exe.to_string(), shell_words::split(&args).ok()?));
+            let exe = parts.remove(0);
+            return Some((exe, parts));
         }
     };
 
     let exe_path = exe_path_str.trim().to_string();
-    let args = shell_words::split(args_str.trim()).unwrap_or_default();
+    let args = split_arguments(args_str);
 
     Some((exe_path, args))
 }
 
 fn get_program_name_from_command(command_str: &str) -> Option<String> {
+    // The last path segment of a URL is usually just an ID, the whole URL reads better
+    if is_url(command_str) {
+        return Some(command_str.trim().to_string());
+    }
     get_executable_and_args_from_command(command_str)
         .and_then(|(exe_path, _)| Path::new(&exe_path).file_name()?.to_str().map(String::from))
 }
 
 
-fn build_command(path: &str) -> io::Result<Command> {
-    match get_executable_and_args_from_command(path) {
+// Prefers the structured exe/args fields, falling back to parsing the combined path
+fn get_program_command(program: &ProgramToLaunch) -> Option<(String, Vec<String>)> {
+    if program.exe_path.is_empty() {
+        get_executable_and_args_from_command(&program.path)
+    } else {
+        Some((
+            expand_path_variables(&program.exe_path),
+            program.args.iter().map(|arg| expand_path_variables(arg)).collect(),
+        ))
+    }
+}
+
+fn get_program_process_name(program: &ProgramToLaunch) -> Option<String> {
+    get_program_command(program)
+        .and_then(|(exe_path, _)| Path::new(&exe_path).file_name()?.to_str().map(String::from))
+}
+
+// Splits the combined path into exe_path/args; only succeeds when the executable
+// resolves to an existing file, otherwise the combined path is kept as the fallback
+fn split_program_command(program: &mut ProgramToLaunch) -> bool {
+    // Splitting stores the expanded path, which would tie the config to this machine
+    if program.path.contains('%') || PATH_TOKENS.iter().any(|(token, _)| program.path.contains(token)) {
+        return false;
+    }
+    match get_executable_and_args_from_command(&program.path) {
+        Some((exe_path, args)) if Path::new(&exe_path).is_file() => {
+            program.exe_path = exe_path;
+            program.args = args;
+            // Keep the shortcut's "Start in" folder, it's lost once the target is stored
+            if program.working_dir.is_none() && is_shortcut(&program.path) {
+                program.working_dir = resolve_shortcut(&program.path).and_then(|target| target.working_dir);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+fn build_command(program: &ProgramToLaunch) -> io::Result<Command> {
+    match get_program_command(program) {
         Some((exe, args)) => {
-            let mut command = Command::new(exe);
-            command.args(args);
+            let mut command = match interpreter_for(&exe) {
+                Some(interpreter) => {
+                    let (program_exe, program_args) = script_invocation(&interpreter, &exe, &args);
+                    let mut command = Command::new(program_exe);
+                    command.args(program_args);
+                    command
+                }
+                // Spawning a batch file directly depends on the environment; go through cmd.exe
+                None if is_batch_script(&exe) => {
+                    let mut command = Command::new("cmd.exe");
+                    command.raw_arg(batch_command_line(&exe, &args));
+                    command
+                }
+                None => {
+                    let mut command = Command::new(exe);
+                    command.args(args);
+                    command
+                }
+            };
+            for (key, value) in program.env.iter().filter(|(key, _)| !key.trim().is_empty()) {
+                command.env(key.trim(), value);
+            }
             Ok(command)
         }
         None => Err(io::Error::new(
@@ -223,561 +1310,5989 @@ fn build_command(path: &str) -> io::Result<Command> {
     }
 }
 
-fn is_process_running(process_name: &str) -> bool {
-    let mut sys = SYSTEM_INFO.lock().unwrap();
-    sys.refresh_processes();
-    sys.processes()
-        .values()
-        .any(|p| p.name().eq_ignore_ascii_case(process_name))
+fn record_launched_pid(program_name: &str, pid: u32) {
+    LAUNCHED_PIDS
+        .lock()
+        .unwrap()
+        .entry(program_name.to_string())
+        .or_default()
+        .push(Pid::from_u32(pid));
 }
 
-fn force_launch_process(path: &str) {
-    log::log(
-        LogLevel::Info,
-        "SYSTEM",
-        &format!("Attempting to launch: {}", path),
-    );
-
-    let mut command = match build_command(path) {
-        Ok(cmd) => cmd,
-        Err(e) => {
-            log::log(
-                LogLevel::Critical,
-                "SYSTEM",
-                &format!("Failed to parse command: {}", e),
-            );
-            return;
-        }
-    };
-
-    if let Some((exe_path, _)) = get_executable_and_args_from_command(path) {
-        if let Some(parent_dir) = Path::new(&exe_path).parent() {
-            command.current_dir(parent_dir);
-        }
+// Drops PIDs of processes that have exited since we launched them
+fn prune_launched_pids(sys: &System) {
+    let mut launched = LAUNCHED_PIDS.lock().unwrap();
+    for pids in launched.values_mut() {
+        pids.retain(|pid| sys.process(*pid).is_some());
     }
+    launched.retain(|_, pids| !pids.is_empty());
+}
 
-    if let Err(e) = command.spawn() {
-        log::log(
-            LogLevel::Critical,
-            "SYSTEM",
-            &format!("Failed to launch process: {}", e),
-        );
+// Runs `f` on the shared process list, rescanning first if the last scan is older than
+// `max_age`. `f` must not lock SYSTEM_INFO again.
+fn with_processes<R>(max_age: Duration, f: impl FnOnce(&System) -> R) -> R {
+    let mut snapshot = SYSTEM_INFO.lock().unwrap();
+    if snapshot.refreshed_at.is_none_or(|at| at.elapsed() >= max_age) {
+        snapshot.sys.refresh_processes();
+        snapshot.refreshed_at = Some(Instant::now());
+        prune_launched_pids(&snapshot.sys);
+        refresh_process_usage(&snapshot.sys);
     }
+    f(&snapshot.sys)
 }
-fn launch_process(path: &str) {
-    if let Some(filename) = get_program_name_from_command(path) {
-        if is_process_running(&filename) {
-            *PENDING_LAUNCH_CONFIRMATION.lock().unwrap() = Some(path.to_string());
-        } else {
-            force_launch_process(path);
-        }
-    } else {
-        force_launch_process(path);
+
+fn refresh_process_usage(sys: &System) {
+    let cores = logical_core_count() as f32;
+    let mut usage = ProcessUsage::default();
+    for process in sys.processes().values() {
+        let entry = usage.by_name.entry(process.name().to_lowercase()).or_default();
+        entry.0 += process.cpu_usage() / cores;
+        entry.1 += process.memory();
     }
-}
-fn launch_process_by_name(name: &str) {
-    let config = CONFIG.lock().unwrap();
-    if let Some(program) = config.programs_to_launch.iter().find(|p| p.name == name) {
-        launch_process(&program.path);
-    } else {
-        log::log(
-            LogLevel::Critical,
-            "SYSTEM",
-            &format!("Program with name '{}' not found.", name),
-        );
+    // Runs right after prune_launched_pids, so every listed PID is still alive
+    for (program, pids) in LAUNCHED_PIDS.lock().unwrap().iter() {
+        let entry = usage.by_program.entry(program.clone()).or_default();
+        for process in pids.iter().filter_map(|pid| sys.process(*pid)) {
+            entry.0 += process.cpu_usage() / cores;
+            entry.1 += process.memory();
+        }
     }
+    *PROCESS_USAGE.lock().unwrap() = usage;
 }
 
-// --- Quick Access & Icon Management ---
-fn create_placeholder_icon(path: &Path) {
-    image::RgbaImage::new(32, 32)
-        .save_with_format(path, image::ImageFormat::Png)
-        .ok();
+fn process_scan_ttl() -> Duration {
+    Duration::from_millis(PROCESS_SCAN_TTL_MS.load(Ordering::Relaxed))
 }
-fn extract_and_save_icon(exe_path: &str, save_path: &Path) -> Result<(), String> {
-    let base64_str = get_icon_base64_by_path(exe_path).map_err(|e| e.to_string())?;
-    let image_data = BASE64.decode(base64_str).map_err(|e| e.to_string())?;
-    let img = image::load(Cursor::new(&image_data), image::ImageFormat::Png)
-        .map_err(|e| e.to_string())?;
-    img.save(save_path).map_err(|e| e.to_string())
+
+fn is_process_running(process_name: &str) -> bool {
+    with_processes(process_scan_ttl(), |sys| {
+        sys.processes()
+            .values()
+            .any(|p| p.name().eq_ignore_ascii_case(process_name))
+    })
 }
-fn setup_quick_access_for_program(program: &ProgramToLaunch) {
-    let addon_dir = match get_addon_dir(env!("CARGO_PKG_NAME")) {
-        Some(dir) => dir,
-        None => return,
-    };
-    let icons_dir = addon_dir.join("icons");
-    fs::create_dir_all(&icons_dir).ok();
 
-    let icon_path = icons_dir.join(format!("{}.png", program.name));
-    if !icon_path.exists() {
-        if let Some((exe_path, _)) = get_executable_and_args_from_command(&program.path) {
-            if let Err(e) = extract_and_save_icon(&exe_path, &icon_path) {
-                log::log(
-                    LogLevel::Warning,
-                    "SYSTEM",
-                    &format!(
-                        "Could not extract icon for {}: {}. Using placeholder.",
-                        program.display_name, e
-                    ),
-                );
-                create_placeholder_icon(&icon_path);
-            }
-        } else {
-            create_placeholder_icon(&icon_path);
-        }
+// The options UI asks every frame, so answers are reused for a short while
+const RUNNING_STATUS_TTL: Duration = Duration::from_secs(2);
+
+fn is_process_running_cached(process_name: &str) -> bool {
+    let mut status = RUNNING_STATUS.lock().unwrap();
+    if status.0.elapsed() >= RUNNING_STATUS_TTL {
+        *status = (Instant::now(), HashMap::new());
     }
+    *status
+        .1
+        .entry(process_name.to_lowercase())
+        .or_insert_with(|| is_process_running(process_name))
+}
 
-    let qa_item_id = format!("QA_ITEM_{}", program.name);
-    let qa_tex_id = format!("QA_TEX_{}", program.name);
+// Usage of the PIDs we launched for the program, or of every process with its name if we
+// have none, as of the last process scan
+fn process_usage(program_name: &str, process_name: &str) -> Option<(f32, u64)> {
+    let usage = PROCESS_USAGE.lock().unwrap();
+    usage
+        .by_program
+        .get(program_name)
+        .or_else(|| usage.by_name.get(&process_name.to_lowercase()))
+        .copied()
+}
 
-    get_texture_or_create_from_file(&qa_tex_id, &icon_path);
-    ICON_CACHE
-        .lock()
-        .unwrap()
-        .insert(program.name.clone(), icon_path);
+fn format_memory(bytes: u64) -> String {
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else {
+        format!("{:.0} MB", mb)
+    }
+}
 
-    if program.show_in_quick_access {
-        add_quick_access(
-            &qa_item_id,
-            &qa_tex_id,
-            &qa_tex_id,
-            &format!("LAUNCH_{}", program.name),
-            &program.display_name,
-        ).revert_on_unload();
+fn show_window_command(state: WindowState) -> SHOW_WINDOW_CMD {
+    match state {
+        WindowState::Normal => SW_SHOWNORMAL,
+        // Don't steal focus from the game
+        WindowState::Minimized => SW_SHOWMINNOACTIVE,
+        WindowState::Maximized => SW_SHOWMAXIMIZED,
+        WindowState::Hidden => SW_HIDE,
     }
 }
-fn teardown_quick_access_for_program(program: &ProgramToLaunch) {
-    let qa_item_id = format!("QA_ITEM_{}", program.name);
-    remove_quick_access(&qa_item_id);
 
-    if let Some(path) = ICON_CACHE.lock().unwrap().remove(&program.name) {
-        fs::remove_file(path).ok();
+fn shell_execute(
+    verb: &str,
+    file: &str,
+    parameters: &str,
+    directory: Option<&Path>,
+    show_cmd: SHOW_WINDOW_CMD,
+) -> io::Result<()> {
+    let verb = to_wide(verb);
+    let file = to_wide(file);
+    let parameters = to_wide(parameters);
+    let directory = directory.map(|dir| to_wide(&dir.to_string_lossy()));
+    let result = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            verb.as_ptr(),
+            file.as_ptr(),
+            parameters.as_ptr(),
+            directory.as_ref().map_or(std::ptr::null(), |dir| dir.as_ptr()),
+            show_cmd,
+        )
+    };
+    // ShellExecuteW reports success with a value greater than 32
+    if result as isize > 32 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
     }
 }
 
-// --- Core Logic ---
-extern "C-unwind" fn keybind_callback(identifier: *const c_char, is_release: bool) {
-    if is_release || identifier.is_null() {
+// Hands documents, bookmarks and URLs to whatever the shell associates with them
+fn open_with_default_application(document: &str, window_state: WindowState) -> io::Result<()> {
+    let dir = if is_url(document) { None } else { Path::new(document).parent() };
+    shell_execute("open", document, "", dir, show_window_command(window_state))
+}
+
+// Opens Explorer with the program's file selected
+fn open_containing_folder(program: &ProgramToLaunch) {
+    let Some((exe_path, _)) = get_program_command(program) else {
+        return;
+    };
+    if !Path::new(&exe_path).exists() {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Cannot open folder for {}: {} does not exist", program.display_name, exe_path),
+        );
         return;
     }
-    let result = panic::catch_unwind(|| {
-        let identifier_cstr = unsafe { CStr::from_ptr(identifier) };
-        if let Ok(id_str) = identifier_cstr.to_str() {
-            if let Some(name) = id_str.strip_prefix("LAUNCH_") {
-                launch_process_by_name(name);
-            }
-        }
-    });
-    if result.is_err() {
+    // Explorer expects the path glued to /select, so it has to bypass Rust's quoting
+    if let Err(e) = Command::new("explorer.exe")
+        .raw_arg(format!("/select,\"{}\"", exe_path))
+        .spawn()
+    {
         log::log(
-            LogLevel::Critical,
+            LogLevel::Warning,
             "SYSTEM",
-            "Panic caught in keybind handler!",
+            &format!("Failed to open Explorer: {}", e),
         );
     }
 }
-fn get_config_path() -> PathBuf {
-    get_addon_dir(env!("CARGO_PKG_NAME"))
-        .expect("Addon directory should exist")
-        .join("settings.ron")
-}
-fn load_config_from_file() {
-    let path = get_config_path();
-    
-    let loaded_config = match fs::read_to_string(&path) {
-        Ok(content) => {
-            log::log(LogLevel::Info, "SYSTEM", "Loading configuration file...");
-            
-            // First try to load as new format
-            match ron::from_str::<Config>(&content) {
-                Ok(config) => {
-                    log::log(LogLevel::Info, "SYSTEM", "Configuration loaded successfully (new format)");
-                    config
-                }
-                Err(_) => {
-                    // Try legacy format
-                    log::log(LogLevel::Info, "SYSTEM", "Trying legacy configuration format...");
-                    match ron::from_str::<LegacyConfig>(&content) {
-                        Ok(legacy_config) => {
-                            log::log(LogLevel::Info, "SYSTEM", "Legacy configuration loaded, converting to new format");
-                            let new_config = Config::from(legacy_config);
-                            
-                            // Save the converted config immediately
-                            let config_to_save = new_config.clone();
-                            *CONFIG.lock().unwrap() = new_config.clone();
-                            drop(CONFIG.lock().unwrap()); // Make sure we release the lock
-                            
-                            // Save in new format
-                            let save_path = get_config_path();
-                            if let Some(parent) = save_path.parent() {
-                                fs::create_dir_all(parent).ok();
-                            }
-                            if let Ok(serialized) = ron::ser::to_string_pretty(&config_to_save, ron::ser::PrettyConfig::default()) {
-                                fs::write(save_path, serialized).ok();
-                            }
-                            
-                            new_config
-                        }
-                        Err(e) => {
-                            log::log(
-                                LogLevel::Warning,
-                                "SYSTEM",
-                                &format!("Failed to parse config file as legacy format: {}. Using defaults.", e),
-                            );
-                            
-                            // Backup the corrupted config
-                            let backup_path = path.with_extension("ron.backup");
-                            if fs::copy(&path, &backup_path).is_ok() {
-                                log::log(LogLevel::Info, "SYSTEM", "Backed up corrupted config to settings.ron.backup");
-                            }
-                            
-                            Config::default()
-                        }
-                    }
-                }
-            }
+
+// The configured working directory if it exists, otherwise the executable's folder
+fn get_working_dir(program: &ProgramToLaunch) -> Option<PathBuf> {
+    if let Some(dir) = &program.working_dir {
+        if Path::new(dir).is_dir() {
+            return Some(PathBuf::from(dir));
         }
-        Err(_) => {
-            log::log(LogLevel::Info, "SYSTEM", "No configuration file found, using defaults");
-            Config::default()
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "Working directory '{}' for {} does not exist, using the executable's folder",
+                dir, program.display_name
+            ),
+        );
+    }
+    if program.exe_path.is_empty() && is_shortcut(&program.path) {
+        if let Some(dir) = resolve_shortcut(&program.path).and_then(|target| target.working_dir) {
+            return Some(PathBuf::from(dir));
         }
-    };
-    
-    *CONFIG.lock().unwrap() = loaded_config;
+    }
+    let (exe_path, _) = get_program_command(program)?;
+    Path::new(&exe_path).parent().map(Path::to_path_buf)
 }
 
-fn save_config_to_file() {
-    let path = get_config_path();
-    if let Some(parent) = path.parent() {
-        if let Err(e) = fs::create_dir_all(parent) {
+// Launches through the "runas" verb so Windows shows the UAC prompt
+fn launch_elevated(program: &ProgramToLaunch) {
+    let Some((exe_path, args)) = get_program_command(program) else {
+        log::log(
+            LogLevel::Critical,
+            "SYSTEM",
+            "Failed to parse command: Empty or invalid command path",
+        );
+        return;
+    };
+    if !program.env.is_empty() {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "Environment variables are not applied to elevated launches ({})",
+                program.display_name
+            ),
+        );
+    }
+    if program.capture_output {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Output of elevated launches can't be captured ({})", program.display_name),
+        );
+    }
+
+    // Scripts run elevated through their interpreter too, not their file association
+    let (file, args) = match interpreter_for(&exe_path) {
+        Some(interpreter) => script_invocation(&interpreter, &exe_path, &args),
+        None => (exe_path.clone(), args),
+    };
+
+    let working_dir = get_working_dir(program);
+    match shell_execute(
+        "runas",
+        &file,
+        &join_arguments(&args),
+        working_dir.as_deref(),
+        show_window_command(program.window_state),
+    ) {
+        Ok(()) => {
+            on_launch_succeeded(program);
+            record_event(&program.display_name, format!("Launched as administrator: {}", exe_path));
+        }
+        Err(e) if e.raw_os_error() == Some(ERROR_CANCELLED as i32) => {
             log::log(
-                LogLevel::Critical,
+                LogLevel::Warning,
                 "SYSTEM",
-                &format!("Failed to create config directory: {}", e),
+                &format!("Elevation was declined for {}, not launched", program.display_name),
             );
-            return;
-        }
-    }
-    
-    let config = CONFIG.lock().unwrap();
-    match ron::ser::to_string_pretty(&*config, ron::ser::PrettyConfig::default()) {
-        Ok(serialized) => {
-            if let Err(e) = fs::write(&path, serialized) {
-                log::log(
-                    LogLevel::Critical,
-                    "SYSTEM",
-                    &format!("Failed to write config file: {}", e),
-                );
-            } else {
-                log::log(LogLevel::Info, "SYSTEM", "Configuration saved successfully");
-            }
+            record_event(&program.display_name, "Elevation declined, not launched".to_string());
         }
         Err(e) => {
             log::log(
-                LogLevel::Critical,
+                LogLevel::Warning,
                 "SYSTEM",
-                &format!("Failed to serialize config: {}", e),
+                &format!("Failed to launch {} as administrator: {}", program.display_name, e),
             );
+            record_event(&program.display_name, format!("Failed to launch as administrator: {}", e));
+            set_launch_error(program, "Failed to launch as administrator", &e);
         }
     }
 }
 
-fn validate_and_cleanup_config() {
-    let mut config = CONFIG.lock().unwrap();
-    let mut needs_save = false;
-    let mut used_names = HashSet::new();
+// Not found and access denied are the usual culprits, so they are spelled out with the code
+fn describe_launch_error(e: &io::Error) -> String {
+    let reason = match e.kind() {
+        io::ErrorKind::NotFound => "file not found",
+        io::ErrorKind::PermissionDenied => "access denied",
+        _ => return e.to_string(),
+    };
+    match e.raw_os_error() {
+        Some(code) => format!("{} (OS error {})", reason, code),
+        None => reason.to_string(),
+    }
+}
 
-    log::log(LogLevel::Info, "SYSTEM", "Validating configuration...");
+fn set_launch_error(program: &ProgramToLaunch, what: &str, e: &io::Error) {
+    LAUNCH_ERRORS
+        .lock()
+        .unwrap()
+        .insert(program.name.clone(), format!("{}: {}", what, describe_launch_error(e)));
+}
 
-    // Clean up and validate programs
-    config.programs_to_launch.retain_mut(|prog| {
-        // Validate path exists (basic check)
-        if let Some((exe_path, _)) = get_executable_and_args_from_command(&prog.path) {
-            if !Path::new(&exe_path).exists() {
+const HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+// Unload runs its hooks on the game's thread, one after the other
+const UNLOAD_HOOK_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Runs a pre-launch or post-kill command to completion. Returns false if it couldn't
+// start, timed out or exited with a non-zero code. A hook started before unload is
+// stopped once unload begins; one started by unload itself gets UNLOAD_HOOK_TIMEOUT.
+fn run_hook(kind: &str, display_name: &str, hook: &str, env: &HashMap<String, String>) -> bool {
+    let started_by_unload = UNLOADING.load(Ordering::SeqCst);
+    let timeout = if started_by_unload { UNLOAD_HOOK_TIMEOUT } else { HOOK_TIMEOUT };
+    let hook_program = ProgramToLaunch {
+        path: hook.to_string(),
+        env: env.clone(),
+        ..Default::default()
+    };
+    let mut command = match build_command(&hook_program) {
+        Ok(command) => command,
+        Err(e) => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Invalid {} command for {}: {}", kind, display_name, e),
+            );
+            return false;
+        }
+    };
+    if let Some(working_dir) = get_working_dir(&hook_program) {
+        command.current_dir(working_dir);
+    }
+    command.creation_flags(CREATE_NO_WINDOW);
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Failed to run {} command for {}: {}", kind, display_name, e),
+            );
+            return false;
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !started_by_unload && UNLOADING.load(Ordering::SeqCst) {
+            let _ = child.kill();
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Stopped {} command for {}, the addon is unloading", kind, display_name),
+            );
+            return false;
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                let level = if status.success() { LogLevel::Info } else { LogLevel::Warning };
+                log::log(
+                    level,
+                    "SYSTEM",
+                    &format!("{} command for {} exited: {}", kind, display_name, status),
+                );
+                return status.success();
+            }
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            _ => {
+                let _ = child.kill();
                 log::log(
                     LogLevel::Warning,
                     "SYSTEM",
-                    &format!("Removing program with non-existent path: {}", prog.path),
+                    &format!(
+                        "{} command for {} did not finish within {}s, killed it",
+                        kind,
+                        display_name,
+                        timeout.as_secs()
+                    ),
                 );
                 return false;
             }
         }
+    }
+}
 
-        // Ensure display_name is set
-        if prog.display_name.is_empty() {
-            if let Some(base_name) = get_program_name_from_command(&prog.path) {
-                prog.display_name = base_name;
-                needs_save = true;
-            } else {
-                prog.display_name = prog.name.clone();
-                needs_save = true;
-            }
-        }
-
-        // Ensure name is properly sanitized and unique
-        if prog.name.is_empty() {
-            if let Some(base_name) = get_program_name_from_command(&prog.path) {
-                prog.name = sanitize_identifier(&base_name);
-                needs_save = true;
-            } else {
-                prog.name = sanitize_identifier(&prog.display_name);
-                needs_save = true;
+fn force_launch_process(program: &ProgramToLaunch) {
+    // Every launch path ends up here: load, keybinds, Quick Access and keep alive. Unload
+    // starts its programs detached, see unload().
+    if !launch_allowed(program) {
+        return;
+    }
+    // The hook may take up to HOOK_TIMEOUT, too long for the render thread or a keybind
+    if program.pre_launch.is_some() {
+        let program = program.clone();
+        spawn_background_task(move || {
+            if run_pre_launch_hook(&program) {
+                spawn_program(&program);
             }
-        }
-
-        // Ensure uniqueness
-        let base_name = prog.name.clone();
-        let mut final_name = base_name.clone();
-        let mut suffix = 2;
-        
-        while used_names.contains(&final_name) {
-            final_name = format!("{}_{}", base_name, suffix);
-            suffix += 1;
-        }
-        
-        if final_name != prog.name {
-            prog.name = final_name;
-            needs_save = true;
-        }
-        
-        used_names.insert(prog.name.clone());
-        true
-    });
-
-    if needs_save {
-        drop(config); // Release lock before saving
-        log::log(LogLevel::Info, "SYSTEM", "Configuration updated, saving...");
-        save_config_to_file();
-    } else {
-        log::log(LogLevel::Info, "SYSTEM", "Configuration is valid");
+        });
+        return;
     }
+    spawn_program(program);
 }
 
-fn load() {
-    // Load config with backwards compatibility
-    load_config_from_file();
-    
-    // Validate and cleanup
-    validate_and_cleanup_config();
+// Checks the master switch and logs the attempt; in a dry run the launch stops here
+fn launch_allowed(program: &ProgramToLaunch) -> bool {
+    let (launches_enabled, dry_run) = {
+        let config = CONFIG.lock().unwrap();
+        (config.launches_enabled, config.dry_run)
+    };
+    if !launches_enabled {
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Launch suppressed (master switch off): {}", program.display_name),
+        );
+        return false;
+    }
 
     log::log(
         LogLevel::Info,
         "SYSTEM",
-        "Loading Assisted Deployment and Departure...",
+        &format!("Attempting to launch: {}", program.path),
     );
+    if dry_run {
+        log_dry_run_launch(program);
+        return false;
+    }
+    true
+}
 
-    // Clear any existing quick access items first
-    let config = CONFIG.lock().unwrap().clone();
-    for program in &config.programs_to_launch {
-        remove_quick_access(&format!("QA_ITEM_{}", program.name));
+// Runs the pre-launch command, if any. False if the launch should not go ahead.
+fn run_pre_launch_hook(program: &ProgramToLaunch) -> bool {
+    let Some(hook) = &program.pre_launch else {
+        return true;
+    };
+    if !run_hook("Pre-launch", &program.display_name, hook, &program.env) && program.abort_on_hook_failure {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Not launching {}: its pre-launch command failed", program.display_name),
+        );
+        record_event(&program.display_name, "Pre-launch command failed, not launched".to_string());
+        return false;
     }
-    
-    // Setup programs
-    for program in &config.programs_to_launch {
+    true
+}
+
+fn spawn_program(program: &ProgramToLaunch) {
+    // A hook or queue thread finishing during unload must not start anything new
+    if UNLOADING.load(Ordering::SeqCst) {
         log::log(
             LogLevel::Info,
             "SYSTEM",
-            &format!("Setting up program: {} ({})", program.display_name, program.name),
+            &format!("Not launching {}, the addon is unloading", program.display_name),
         );
-        
-        register_keybind_with_string(
-            format!("LAUNCH_{}", program.name), 
-            keybind_callback, 
-            ""
-        ).revert_on_unload();
-        
-        setup_quick_access_for_program(&program);
-        
-        if program.trigger == LaunchTrigger::OnAddonLoad {
-            launch_process(&program.path);
-        }
+        return;
     }
-    
-    register_render(RenderType::OptionsRender, render!(render_options)).revert_on_unload();
-    register_render(RenderType::Render, render!(render_popup)).revert_on_unload();
+    start_program(program, false);
 }
 
-fn unload() {
-    save_config_to_file();
+// The command for a plain (not elevated, not document) launch, with its working folder,
+// window and output settings applied
+fn program_command(program: &ProgramToLaunch, detached: bool) -> io::Result<Command> {
+    let mut command = build_command(program)?;
+    if let Some(working_dir) = get_working_dir(program) {
+        command.current_dir(working_dir);
+    }
+    if program.window_state == WindowState::Hidden {
+        // Covers console programs; GUI windows are hidden once they appear
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    if program.capture_output && !detached {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    Ok(command)
+}
 
-    let kill_list = {
-        let config = CONFIG.lock().unwrap();
-        let mut list = config.programs_to_kill.clone();
-        for program in &config.programs_to_launch {
-            if program.close_on_unload {
-                if let Some(filename) = get_program_name_from_command(&program.path) {
-                    if !list.contains(&filename) {
-                        list.push(filename);
-                    }
-                }
+// A detached launch only starts the program: no output capture, retries or watchers
+fn start_program(program: &ProgramToLaunch, detached: bool) {
+    let path = program.path.as_str();
+    set_keep_alive_paused(&program.name, false);
+
+    if let Some(document) = get_document_path(path) {
+        match open_with_default_application(&document, program.window_state) {
+            Ok(()) => {
+                on_launch_succeeded(program);
+                record_event(&program.display_name, format!("Opened {}", document));
+            }
+            Err(e) => {
+                log::log(
+                    LogLevel::Critical,
+                    "SYSTEM",
+                    &format!("Failed to open document: {}", e),
+                );
+                record_event(&program.display_name, format!("Failed to open: {}", e));
+                set_launch_error(program, "Failed to open", &e);
             }
         }
-        list
+        return;
+    }
+
+    if program.run_as_admin {
+        launch_elevated(program);
+        return;
+    }
+
+    let mut command = match program_command(program, detached) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            log::log(
+                LogLevel::Critical,
+                "SYSTEM",
+                &format!("Failed to parse command: {}", e),
+            );
+            set_launch_error(program, "Failed to parse command", &e);
+            return;
+        }
     };
 
-    if !kill_list.is_empty() {
-        cleanup_processes(&kill_list);
+    match command.spawn() {
+        Ok(child) if detached => {
+            on_launch_succeeded(program);
+            record_event(&program.display_name, format!("Launched (PID: {}): {}", child.id(), program.path));
+        }
+        Ok(child) => on_program_spawned(program, child, 1),
+        Err(e) if program.launch_retries > 0 && !detached => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!(
+                    "Failed to launch {} (attempt 1 of {}): {}",
+                    program.display_name,
+                    program.launch_retries + 1,
+                    e
+                ),
+            );
+            retry_launch(program.clone(), command, 2);
+        }
+        Err(e) => {
+            log::log(
+                LogLevel::Critical,
+                "SYSTEM",
+                &format!("Failed to launch process: {}", e),
+            );
+            record_event(&program.display_name, format!("Failed to launch: {}", e));
+            set_launch_error(program, "Failed to launch", &e);
+        }
     }
-    log::log(LogLevel::Info, "SYSTEM", "Unloaded.");
 }
-fn cleanup_processes(targets: &[String]) {
-    let safe_targets: Vec<_> = targets
-        .iter()
-        .filter(|n| !n.eq_ignore_ascii_case("Gw2-64.exe"))
-        .collect();
-    if safe_targets.is_empty() {
+// Bookkeeping shared by every way a launch can succeed; failed attempts leave all of it alone
+fn on_launch_succeeded(program: &ProgramToLaunch) {
+    LAUNCH_ERRORS.lock().unwrap().remove(&program.name);
+    count_launch(&program.name);
+    if program.launch_once_per_session {
+        mark_launched_this_session(&program.name);
+    }
+}
+// `attempt` counts from 1; a later attempt is a retry through retry_launch
+fn on_program_spawned(program: &ProgramToLaunch, mut child: Child, attempt: u32) {
+    on_launch_succeeded(program);
+    if let Some(stdout) = child.stdout.take() {
+        forward_program_output(program.display_name.clone(), false, stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_program_output(program.display_name.clone(), true, stderr);
+    }
+    record_event(&program.display_name, format!("Launched (PID: {}): {}", child.id(), program.path));
+    record_launched_pid(&program.name, child.id());
+    if program.window_state != WindowState::Normal {
+        apply_window_state(child.id(), program.window_state);
+    }
+    if program.priority != ProcessPriority::Normal {
+        apply_priority(program, &child);
+    }
+    if let Some(mask) = program.cpu_affinity {
+        apply_affinity(program, &child, mask);
+    }
+    EXIT_CODES.lock().unwrap().insert(program.name.clone(), None);
+    if let Some(secs) = program.auto_close_after_secs {
+        start_auto_close_timer(program, child.id(), Duration::from_secs(secs));
+    }
+    watch_launched_child(program.clone(), child, attempt);
+}
+// Runs the remaining attempts, from `first_attempt` on, in the background so the delays
+// don't stall the game
+fn retry_launch(program: ProgramToLaunch, mut command: Command, first_attempt: u32) {
+    spawn_background_task(move || {
+        let attempts = program.launch_retries + 1;
+        for attempt in first_attempt..=attempts {
+            if !sleep_unless_unloading(Duration::from_millis(program.retry_delay_ms)) {
+                return;
+            }
+            match command.spawn() {
+                Ok(child) => {
+                    log::log(
+                        LogLevel::Info,
+                        "SYSTEM",
+                        &format!("Launched {} on attempt {} of {}", program.display_name, attempt, attempts),
+                    );
+                    on_program_spawned(&program, child, attempt);
+                    return;
+                }
+                Err(e) if attempt < attempts => {
+                    log::log(
+                        LogLevel::Warning,
+                        "SYSTEM",
+                        &format!(
+                            "Failed to launch {} (attempt {} of {}): {}",
+                            program.display_name, attempt, attempts, e
+                        ),
+                    );
+                }
+                Err(e) => {
+                    log::log(
+                        LogLevel::Critical,
+                        "SYSTEM",
+                        &format!("Failed to launch process after {} attempts: {}", attempts, e),
+                    );
+                    record_event(
+                        &program.display_name,
+                        format!("Failed to launch after {} attempts: {}", attempts, e),
+                    );
+                    set_launch_error(&program, &format!("Failed to launch after {} attempts", attempts), &e);
+                }
+            }
+        }
+    });
+}
+// `program` is usually a clone, so the count is bumped on the entry in CONFIG. It is written
+// by the next save, at the latest on unload.
+fn count_launch(name: &str) {
+    let mut config = CONFIG.lock().unwrap();
+    if let Some(prog) = config.programs_to_launch.iter_mut().find(|p| p.name == name) {
+        prog.launch_count += 1;
+        *UNSAVED_LAUNCHES.lock().unwrap().entry(name.to_string()).or_default() += 1;
+    }
+}
+fn log_dry_run_launch(program: &ProgramToLaunch) {
+    let message = if let Some(document) = get_document_path(&program.path) {
+        format!("[Dry run] Would open document: {}", document)
+    } else if let Some((exe_path, mut args)) = get_program_command(program) {
+        for &index in &program.sensitive_args {
+            if let Some(arg) = args.get_mut(index) {
+                *arg = MASKED_ARG.to_string();
+            }
+        }
+        format!(
+            "[Dry run] Would launch: {} {} (working dir: {}, as administrator: {}, env vars: {})",
+            quote_argument(&exe_path),
+            join_arguments(&args),
+            get_working_dir(program).map_or_else(|| "(none)".to_string(), |dir| dir.display().to_string()),
+            program.run_as_admin,
+            program.env.len()
+        )
+    } else {
+        format!("[Dry run] Could not parse command: {}", program.path)
+    };
+    log::log(LogLevel::Info, "SYSTEM", &message);
+}
+fn launch_process(program: &ProgramToLaunch) {
+    if defer_if_loading(program) {
+        return;
+    }
+    if program.active_in != GameMode::Any && current_game_mode() != Some(program.active_in) {
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Not launching {}, it is only active in another game mode", program.display_name),
+        );
         return;
     }
 
-    log::log(
-        LogLevel::Info,
-        "SYSTEM",
-        &format!("Closing processes: {:?}", safe_targets),
-    );
-    let mut sys = System::new_all();
-    sys.refresh_processes();
-    for target in safe_targets {
-        for p in sys
-            .processes()
-            .values()
-            .filter(|p| p.name().eq_ignore_ascii_case(target))
-        {
+    if let Some(required) = &program.require_process {
+        if !is_process_running(required) {
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!("Not launching {}, {} is not running", program.display_name, required),
+            );
+            return;
+        }
+    }
+    if let Some(conflicting) = &program.skip_if_process {
+        if is_process_running(conflicting) {
             log::log(
                 LogLevel::Info,
                 "SYSTEM",
-                &format!("Killing: {} (PID: {})", p.name(), p.pid()),
+                &format!("Not launching {}, {} is running", program.display_name, conflicting),
+            );
+            return;
+        }
+    }
+
+    if let Some(required) = &program.require_file {
+        let expanded = expand_path_variables(required);
+        if !Path::new(expanded.trim().trim_matches('"')).exists() {
+            // Only the first skip is a warning, the same launch is often triggered again and again
+            let first = REQUIRE_FILE_WARNED.lock().unwrap().insert(program.name.clone());
+            let message = program
+                .require_file_message
+                .clone()
+                .unwrap_or_else(|| format!("{} is missing", expanded));
+            log::log(
+                if first { LogLevel::Warning } else { LogLevel::Debug },
+                "SYSTEM",
+                &format!("Not launching {}: {}", program.display_name, message),
             );
-            p.kill();
+            return;
         }
     }
+
+    if let Some(url) = program.precheck_url.clone() {
+        // A slow or dead server must not stall the game while we wait for the connection
+        let program = program.clone();
+        spawn_background_task(move || {
+            let timeout = Duration::from_millis(CONFIG.lock().unwrap().precheck_timeout_ms);
+            match check_reachable(&url, timeout) {
+                Ok(()) => launch_if_not_running(&program),
+                Err(e) => log::log(
+                    LogLevel::Info,
+                    "SYSTEM",
+                    &format!("Not launching {}, {} is not reachable: {}", program.display_name, url, e),
+                ),
+            }
+        });
+        return;
+    }
+    launch_if_not_running(program);
 }
+// Applies the program's "If already running" choice, launching if it isn't running
+fn launch_if_not_running(program: &ProgramToLaunch) {
+    // Documents open in whatever app is associated, so there is no process of ours to check
+    if get_document_path(&program.path).is_some() {
+        force_launch_process(program);
+        return;
+    }
 
-// --- UI Rendering ---
-fn render_popup(ui: &Ui) {
-    let mut pending_launch = PENDING_LAUNCH_CONFIRMATION.lock().unwrap();
-    let mut close_popup = false;
-    let path_to_launch = pending_launch.clone();
-    if let Some(path) = path_to_launch {
-        let filename = get_program_name_from_command(&path).unwrap_or_else(|| "program".to_string());
-        let mut open = true;
-        Window::new(&format!("'{}' Already Running", filename))
-            .opened(&mut open)
-            .always_auto_resize(true)
-            .collapsible(false)
-            .focus_on_appearing(true)
-            .build(ui, || {
-                ui.text("This program is already running.");
-                ui.text("Do you want to open another instance?");
-                ui.separator();
-                if ui.button("Yes") {
-                    force_launch_process(&path);
-                    close_popup = true;
+    if let Some(filename) = get_program_process_name(program) {
+        if is_process_running(&filename) {
+            match program.on_already_running {
+                AlreadyRunningAction::Ask => {
+                    *PENDING_LAUNCH_CONFIRMATION.lock().unwrap() = Some((program.clone(), Instant::now()));
                 }
-                ui.same_line();
-                if ui.button("No") {
-                    close_popup = true;
+                AlreadyRunningAction::FocusExisting => {
+                    if !focus_existing_window(&filename) {
+                        log::log(
+                            LogLevel::Info,
+                            "SYSTEM",
+                            &format!("{} is running but has no window to focus", filename),
+                        );
+                    }
                 }
-            });
-        if !open {
-            close_popup = true;
+                AlreadyRunningAction::LaunchAnyway => force_launch_process(program),
+                AlreadyRunningAction::DoNothing => {}
+            }
+        } else {
+            force_launch_process(program);
         }
+    } else {
+        force_launch_process(program);
     }
-    if close_popup {
-        *pending_launch = None;
+}
+// Host and port to connect to for a precheck: "host:port", or a URL whose port defaults
+// to 80 for http and 443 for https
+fn precheck_address(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = match url.trim().split_once("://") {
+        Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+        None => (None, url.trim()),
+    };
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, Some(port.parse::<u16>().ok()?)),
+        _ => (authority, None),
+    };
+    let port = port.or(match scheme.as_deref() {
+        Some("http") => Some(80),
+        Some("https") => Some(443),
+        _ => None,
+    })?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+// A plain TCP connect, enough to tell whether a local server is up. `timeout` covers the
+// whole check, however many addresses the host resolves to.
+fn check_reachable(url: &str, timeout: Duration) -> Result<(), String> {
+    if UNLOADING.load(Ordering::SeqCst) {
+        return Err("the addon is unloading".to_string());
+    }
+    let (host, port) = precheck_address(url).ok_or("not a host:port or http(s) URL")?;
+    let deadline = Instant::now() + timeout;
+    // IP addresses skip the resolver, which can't be given a timeout
+    let addresses: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => (host.as_str(), port).to_socket_addrs().map_err(|e| e.to_string())?.collect(),
+    };
+    let mut last_error = "host did not resolve".to_string();
+    for address in addresses {
+        if UNLOADING.load(Ordering::SeqCst) {
+            return Err("the addon is unloading".to_string());
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(format!("no connection within {}ms", timeout.as_millis()));
+        }
+        match TcpStream::connect_timeout(&address, remaining) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = e.to_string(),
+        }
     }
+    Err(last_error)
 }
 
-fn render_options(ui: &Ui) {
-    ui.text("Manage external programs to launch/kill.");
-    ui.separator();
-    
-    // Handle Programs to Launch section
-    render_programs_to_launch_section(ui);
-    
-    // Handle Programs to Kill section
-    render_programs_to_kill_section(ui);
+fn find_process_pids(process_name: &str) -> Vec<u32> {
+    with_processes(process_scan_ttl(), |sys| {
+        sys.processes()
+            .values()
+            .filter(|p| p.name().eq_ignore_ascii_case(process_name))
+            .map(|p| p.pid().as_u32())
+            .collect()
+    })
 }
 
-fn render_programs_to_launch_section(ui: &Ui) {
-    if !ui.collapsing_header("Programs to Launch", TreeNodeFlags::DEFAULT_OPEN) {
+// Brings the first running instance with a main window to the foreground
+fn focus_existing_window(process_name: &str) -> bool {
+    for pid in find_process_pids(process_name) {
+        if let Some(hwnd) = find_main_window(pid) {
+            unsafe {
+                if IsIconic(hwnd) != 0 {
+                    ShowWindow(hwnd, SW_RESTORE);
+                }
+                SetForegroundWindow(hwnd);
+            }
+            return true;
+        }
+    }
+    false
+}
+
+// Returns the new generation; timers started before this call no longer fire
+fn cancel_auto_close(name: &str) -> u64 {
+    let mut generations = AUTO_CLOSE_GENERATION.lock().unwrap();
+    let generation = generations.entry(name.to_string()).or_default();
+    *generation += 1;
+    *generation
+}
+
+// Closes the instance with `pid` after `after`, through the program's usual graceful-then-kill
+// path. A newer launch or a restart of the program cancels it, and so does unload.
+fn start_auto_close_timer(program: &ProgramToLaunch, pid: u32, after: Duration) {
+    let generation = cancel_auto_close(&program.name);
+    let Some(target) = KillTarget::for_program(program) else {
         return;
+    };
+    let target = KillTarget {
+        pids: Some(vec![Pid::from_u32(pid)]),
+        ..target
+    };
+    let name = program.name.clone();
+    let display_name = program.display_name.clone();
+    spawn_background_task(move || {
+        if !sleep_unless_unloading(after) {
+            return;
+        }
+        if AUTO_CLOSE_GENERATION.lock().unwrap().get(&name) != Some(&generation) {
+            return;
+        }
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Closing {} (PID: {}), its {}s run time is up", display_name, pid, after.as_secs()),
+        );
+        cleanup_processes(&[target]);
+    });
+}
+
+fn restart_program(program: ProgramToLaunch, process_name: String) {
+    set_keep_alive_paused(&program.name, true);
+    cancel_auto_close(&program.name);
+    spawn_background_task(move || {
+        let mut pids = cleanup_processes(&KillTarget::for_program(&program).into_iter().collect::<Vec<_>>());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        // Only the instances we just closed matter, another copy with the same name may keep running
+        loop {
+            with_processes(Duration::ZERO, |sys| pids.retain(|pid| sys.process(*pid).is_some()));
+            if pids.is_empty() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                log::log(
+                    LogLevel::Warning,
+                    "SYSTEM",
+                    &format!("{} is still running after kill, relaunching anyway", process_name),
+                );
+                break;
+            }
+            if !sleep_unless_unloading(Duration::from_millis(250)) {
+                return;
+            }
+        }
+        force_launch_process(&program);
+    });
+}
+
+// Launches the program, checks that its process showed up and closes that instance again
+// after `run_for`. Retries, auto-close and elevation are left out so the PID is ours to track.
+fn start_test_launch(program: &ProgramToLaunch, process_name: String, run_for: Duration) {
+    let program = ProgramToLaunch {
+        launch_retries: 0,
+        auto_close_after_secs: None,
+        run_as_admin: false,
+        ..program.clone()
+    };
+    TEST_LAUNCHES.lock().unwrap().insert(
+        program.name.clone(),
+        TestLaunch::Running {
+            until: Instant::now() + run_for,
+        },
+    );
+    spawn_background_task(move || {
+        let earlier_pids = LAUNCHED_PIDS.lock().unwrap().get(&program.name).cloned().unwrap_or_default();
+        // Already on a background thread, so the pre-launch hook runs inline and the new
+        // PID is known as soon as this returns
+        if launch_allowed(&program) && run_pre_launch_hook(&program) {
+            spawn_program(&program);
+        }
+        let pid = LAUNCHED_PIDS
+            .lock()
+            .unwrap()
+            .get(&program.name)
+            .and_then(|pids| pids.iter().find(|pid| !earlier_pids.contains(pid)).copied());
+        let Some(pid) = pid else {
+            TEST_LAUNCHES.lock().unwrap().insert(program.name.clone(), TestLaunch::NotStarted);
+            return;
+        };
+        if !sleep_unless_unloading(run_for) {
+            return;
+        }
+        let result = match EXIT_CODES.lock().unwrap().get(&program.name) {
+            Some(Some(code)) => TestLaunch::ExitedEarly(*code),
+            _ => TestLaunch::Finished {
+                pid: pid.as_u32(),
+                seen: is_process_running(&process_name),
+            },
+        };
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Test launch of {} is over, closing PID {}", program.display_name, pid),
+        );
+        // Keep alive would otherwise bring the test instance straight back
+        set_keep_alive_paused(&program.name, true);
+        if let Some(target) = KillTarget::for_program(&program) {
+            cleanup_processes(&[KillTarget {
+                pids: Some(vec![pid]),
+                ..target
+            }]);
+        }
+        TEST_LAUNCHES.lock().unwrap().insert(program.name.clone(), result);
+    });
+}
+
+fn render_test_launch_result(ui: &Ui, result: &TestLaunch, process_name: &str) {
+    match result {
+        TestLaunch::Running { until } => ui.text_colored(
+            [0.6, 0.6, 0.6, 1.0],
+            format!("Testing... {}s", until.saturating_duration_since(Instant::now()).as_secs() + 1),
+        ),
+        TestLaunch::NotStarted => ui.text_colored([0.9, 0.3, 0.3, 1.0], "Test: did not start, see the log"),
+        TestLaunch::ExitedEarly(code) => ui.text_colored(
+            [0.9, 0.3, 0.3, 1.0],
+            format!("Test: started but exited with code {}", code),
+        ),
+        TestLaunch::Finished { pid, seen: true } => ui.text_colored(
+            [0.2, 0.8, 0.2, 1.0],
+            format!("Test: started (PID {}) and {} was running", pid, process_name),
+        ),
+        TestLaunch::Finished { pid, seen: false } => ui.text_colored(
+            [0.9, 0.7, 0.2, 1.0],
+            format!("Test: started (PID {}) but no {} process was found", pid, process_name),
+        ),
+    }
+}
+
+fn toggle_program(program: &ProgramToLaunch, process_name: String) {
+    let now_running = if is_process_running(&process_name) {
+        set_keep_alive_paused(&program.name, true);
+        cleanup_processes_in_background(KillTarget::for_program(program).into_iter().collect());
+        false
+    } else {
+        force_launch_process(program);
+        true
+    };
+    if program.show_in_quick_access && program.triggers.contains(&LaunchTrigger::Toggle) {
+        add_program_quick_access(program, Some(now_running));
+    }
+}
+
+// Runs the program's toggle trigger or configured quick access action
+fn activate_program(program: &ProgramToLaunch) {
+    let process_name = match get_program_process_name(program) {
+        Some(name) if get_document_path(&program.path).is_none() => name,
+        _ => {
+            launch_process(program);
+            return;
+        }
+    };
+
+    if program.triggers.contains(&LaunchTrigger::Toggle) {
+        toggle_program(program, process_name);
+        return;
+    }
+
+    match program.quick_access_action {
+        QuickAccessAction::Launch => launch_process(program),
+        QuickAccessAction::FocusExisting => {
+            if !focus_existing_window(&process_name) {
+                launch_process(program);
+            }
+        }
+        QuickAccessAction::Toggle => toggle_program(program, process_name),
+        QuickAccessAction::Restart => {
+            if is_process_running(&process_name) {
+                restart_program(program.clone(), process_name);
+            } else {
+                force_launch_process(program);
+            }
+        }
+    }
+}
+
+// Launches now, or on a timer thread if the program has a launch delay.
+// Pending timers are cancelled by unload.
+fn schedule_launch(program: &ProgramToLaunch) {
+    if program.launch_delay_ms == 0 {
+        queue_launch(program);
+        return;
+    }
+
+    let program = program.clone();
+    spawn_background_task(move || {
+        if sleep_unless_unloading(Duration::from_millis(program.launch_delay_ms)) {
+            queue_launch(&program);
+        }
+    });
+}
+
+const LAUNCH_BATCH_DELAY: Duration = Duration::from_millis(500);
+
+// Launches right away without a concurrency limit, otherwise hands the program to a
+// worker that starts the queue in batches of max_concurrent_launches
+fn queue_launch(program: &ProgramToLaunch) {
+    if CONFIG.lock().unwrap().max_concurrent_launches == 0 {
+        launch_process(program);
+        return;
+    }
+    {
+        let mut queue = LAUNCH_QUEUE.lock().unwrap();
+        queue.push_back(program.clone());
+        if LAUNCH_QUEUE_ACTIVE.swap(true, Ordering::SeqCst) {
+            return;
+        }
+    }
+
+    spawn_background_task(|| loop {
+        let batch_size = CONFIG.lock().unwrap().max_concurrent_launches.max(1);
+        let batch: Vec<ProgramToLaunch> = {
+            let mut queue = LAUNCH_QUEUE.lock().unwrap();
+            // Whatever is still queued when unloading starts is dropped
+            if queue.is_empty() || UNLOADING.load(Ordering::SeqCst) {
+                queue.clear();
+                LAUNCH_QUEUE_ACTIVE.store(false, Ordering::SeqCst);
+                return;
+            }
+            let count = batch_size.min(queue.len());
+            queue.drain(..count).collect()
+        };
+        for program in &batch {
+            launch_process(program);
+        }
+        sleep_unless_unloading(LAUNCH_BATCH_DELAY);
+    });
+}
+
+// Sorts programs so each one comes after its `launch_after` prerequisite. Prerequisites
+// outside of `programs` are ignored. Programs caught in a cycle are logged and appended
+// in their original order rather than left out.
+fn order_by_dependencies(programs: Vec<ProgramToLaunch>) -> Vec<ProgramToLaunch> {
+    let names: HashSet<String> = programs.iter().map(|p| p.name.clone()).collect();
+    let mut remaining = programs;
+    let mut ordered: Vec<ProgramToLaunch> = Vec::with_capacity(remaining.len());
+
+    loop {
+        let ready = remaining.iter().position(|p| match &p.launch_after {
+            Some(dep) if names.contains(dep) => ordered.iter().any(|o| o.name == *dep),
+            _ => true,
+        });
+        match ready {
+            Some(idx) => ordered.push(remaining.remove(idx)),
+            None => break,
+        }
+    }
+
+    if !remaining.is_empty() {
+        log::log(
+            LogLevel::Critical,
+            "SYSTEM",
+            &format!(
+                "Launch order has a cycle between: {:?}. Launching them unordered.",
+                remaining.iter().map(|p| p.display_name.as_str()).collect::<Vec<_>>()
+            ),
+        );
+        ordered.extend(remaining);
+    }
+    ordered
+}
+
+// Launches the programs one after another in dependency order. Launch delays still
+// count from the start of the sequence, but never let a program overtake its prerequisite.
+fn launch_in_order(programs: Vec<ProgramToLaunch>) {
+    let ordered = order_by_dependencies(programs);
+    if ordered.iter().all(|p| p.launch_after.is_none()) {
+        for program in &ordered {
+            schedule_launch(program);
+        }
+        return;
+    }
+
+    spawn_background_task(move || {
+        let start = Instant::now();
+        for program in &ordered {
+            let launch_at = start + Duration::from_millis(program.launch_delay_ms);
+            if !sleep_unless_unloading(launch_at.saturating_duration_since(Instant::now())) {
+                return;
+            }
+            launch_process(program);
+
+            let has_dependents = ordered.iter().any(|p| p.launch_after.as_deref() == Some(program.name.as_str()));
+            if program.wait_for_ready && has_dependents && !wait_until_running(program) {
+                return;
+            }
+        }
+    });
+}
+
+const READY_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Polls until the program's process shows up. Gives up with a warning after the timeout
+// so dependents still launch. Returns false if the addon is unloading.
+fn wait_until_running(program: &ProgramToLaunch) -> bool {
+    let Some(process_name) = get_program_process_name(program) else {
+        return true;
+    };
+    let deadline = Instant::now() + READY_WAIT_TIMEOUT;
+    while !is_process_running(&process_name) {
+        if Instant::now() >= deadline {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!(
+                    "{} was not running after {}s, launching its dependents anyway",
+                    program.display_name,
+                    READY_WAIT_TIMEOUT.as_secs()
+                ),
+            );
+            return true;
+        }
+        if !sleep_unless_unloading(Duration::from_millis(250)) {
+            return false;
+        }
+    }
+    true
+}
+
+// Appends _2, _3, ... to `base_name` until it doesn't collide with an existing program,
+// since the name is used in keybind and Quick Access IDs
+fn unique_program_name(base_name: &str, programs: &[ProgramToLaunch]) -> String {
+    let mut final_name = base_name.to_string();
+    let mut suffix = 2;
+    while programs.iter().any(|p| p.name == final_name) {
+        final_name = format!("{}_{}", base_name, suffix);
+        suffix += 1;
+    }
+    final_name
+}
+
+// Executable path in a form that compares equal regardless of case and slash direction
+fn normalized_exe_path(program: &ProgramToLaunch) -> Option<String> {
+    if is_url(&program.path) {
+        return None;
+    }
+    get_program_command(program).map(|(exe_path, _)| exe_path.trim().replace('/', "\\").to_lowercase())
+}
+
+// Flags programs that resolve to the same executable as another entry. They're only
+// warned about, since launching the same exe with different arguments can be intended.
+fn refresh_duplicate_paths(programs: &[ProgramToLaunch]) {
+    let mut by_path: HashMap<String, Vec<String>> = HashMap::new();
+    for prog in programs {
+        if let Some(path) = normalized_exe_path(prog) {
+            by_path.entry(path).or_default().push(prog.name.clone());
+        }
+    }
+
+    // Runs on every save, so only pairs that weren't flagged before are logged
+    let mut previous = DUPLICATE_PATHS.lock().unwrap();
+    let mut duplicates = HashMap::new();
+    for (path, names) in by_path.into_iter().filter(|(_, names)| names.len() > 1) {
+        let has_new_pair = names.iter().any(|name| {
+            names
+                .iter()
+                .any(|other| other != name && !previous.get(name).is_some_and(|known| known.contains(other)))
+        });
+        if has_new_pair {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("{} programs launch the same executable: {}", names.len(), path),
+            );
+        }
+        for name in &names {
+            let others = names.iter().filter(|other| *other != name).cloned().collect();
+            duplicates.insert(name.clone(), others);
+        }
+    }
+    *previous = duplicates;
+}
+
+fn launch_process_by_name(name: &str) {
+    let (program, cooldown) = {
+        let config = CONFIG.lock().unwrap();
+        (
+            config.programs_to_launch.iter().find(|p| p.name == name).cloned(),
+            Duration::from_millis(config.launch_cooldown_ms),
+        )
+    };
+    if let Some(program) = program {
+        // Keybinds stay registered for disabled programs but do nothing
+        if !program.enabled {
+            return;
+        }
+
+        if !in_launch_cooldown(&program, cooldown) {
+            activate_program(&program);
+        }
+    } else {
+        log::log(
+            LogLevel::Critical,
+            "SYSTEM",
+            &format!("Program with name '{}' not found.", name),
+        );
+    }
+}
+
+// Repeated presses would launch again before the new process shows up as running.
+// Starts a new cooldown window when the trigger is let through.
+fn in_launch_cooldown(program: &ProgramToLaunch, cooldown: Duration) -> bool {
+    let mut last_triggered = LAST_TRIGGERED.lock().unwrap();
+    if let Some(last) = last_triggered.get(&program.name) {
+        if last.elapsed() < cooldown {
+            log::log(
+                LogLevel::Debug,
+                "SYSTEM",
+                &format!("Ignoring trigger for {}, still in launch cooldown", program.display_name),
+            );
+            return true;
+        }
+    }
+    last_triggered.insert(program.name.clone(), Instant::now());
+    false
+}
+
+// Handler of the "launch all" keybind and tile. Programs that only run on unload (or only
+// have a close trigger) are skipped.
+fn launch_all_programs() {
+    let (programs, cooldown): (Vec<ProgramToLaunch>, Duration) = {
+        let config = CONFIG.lock().unwrap();
+        (
+            config
+                .programs_to_launch
+                .iter()
+                .filter(|p| {
+                    p.enabled
+                        && p.triggers
+                            .iter()
+                            .any(|t| !matches!(t, LaunchTrigger::OnUnload | LaunchTrigger::OnMapLeave(_)))
+                })
+                .cloned()
+                .collect(),
+            Duration::from_millis(config.launch_cooldown_ms),
+        )
+    };
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Launching all programs ({})", programs.len()),
+    );
+    for program in &programs {
+        if !in_launch_cooldown(program, cooldown) {
+            queue_launch(program);
+        }
+    }
+}
+
+// --- Shortcut Resolution ---
+#[derive(Clone)]
+struct ShortcutTarget {
+    exe_path: String,
+    args: Vec<String>,
+    working_dir: Option<String>,
+}
+
+fn is_shortcut(command_str: &str) -> bool {
+    let expanded = expand_path_variables(command_str);
+    let path = Path::new(expanded.trim().trim_matches('"'));
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("lnk")) && path.is_file()
+}
+
+// Resolves a .lnk file once per session; later lookups come from SHORTCUT_CACHE
+fn resolve_shortcut(lnk_path: &str) -> Option<ShortcutTarget> {
+    let expanded = expand_path_variables(lnk_path);
+    let lnk_path = expanded.trim().trim_matches('"');
+    let key = lnk_path.to_lowercase();
+    if let Some(target) = SHORTCUT_CACHE.lock().unwrap().get(&key) {
+        return Some(target.clone());
+    }
+
+    match read_shortcut(lnk_path) {
+        Ok(target) if !target.exe_path.is_empty() => {
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!("Resolved shortcut {} to {}", lnk_path, target.exe_path),
+            );
+            SHORTCUT_CACHE.lock().unwrap().insert(key, target.clone());
+            Some(target)
+        }
+        Ok(_) => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Shortcut {} does not point at a file", lnk_path),
+            );
+            None
+        }
+        Err(e) => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Failed to resolve shortcut {}: {}", lnk_path, e),
+            );
+            None
+        }
+    }
+}
+
+// windows-sys has no COM interfaces, so the few IShellLinkW and IPersistFile methods we call
+// are declared by hand. Slots we never call are placeholders that keep the vtable layout.
+const IID_ISHELLLINKW: GUID = GUID::from_u128(0x000214f9_0000_0000_c000_000000000046);
+const IID_IPERSISTFILE: GUID = GUID::from_u128(0x0000010b_0000_0000_c000_000000000046);
+
+#[repr(C)]
+struct IUnknownVtbl {
+    query_interface: unsafe extern "system" fn(*mut c_void, *const GUID, *mut *mut c_void) -> HRESULT,
+    add_ref: unsafe extern "system" fn(*mut c_void) -> u32,
+    release: unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+#[repr(C)]
+struct IShellLinkWVtbl {
+    base: IUnknownVtbl,
+    get_path: unsafe extern "system" fn(*mut c_void, PWSTR, i32, *mut c_void, u32) -> HRESULT,
+    _get_id_list: usize,
+    _set_id_list: usize,
+    _get_description: usize,
+    _set_description: usize,
+    get_working_directory: unsafe extern "system" fn(*mut c_void, PWSTR, i32) -> HRESULT,
+    _set_working_directory: usize,
+    get_arguments: unsafe extern "system" fn(*mut c_void, PWSTR, i32) -> HRESULT,
+}
+
+#[repr(C)]
+struct IPersistFileVtbl {
+    base: IUnknownVtbl,
+    _get_class_id: usize,
+    _is_dirty: usize,
+    load: unsafe extern "system" fn(*mut c_void, PCWSTR, u32) -> HRESULT,
+}
+
+// Owns one reference to a COM object whose vtable is `V`, released on drop
+struct ComPtr<V>(*mut *const V);
+
+impl<V> ComPtr<V> {
+    unsafe fn vtbl(&self) -> &V {
+        &**self.0
+    }
+
+    fn raw(&self) -> *mut c_void {
+        self.0 as *mut c_void
+    }
+}
+
+impl<V> Drop for ComPtr<V> {
+    fn drop(&mut self) {
+        unsafe {
+            let unknown = *(self.0 as *mut *const IUnknownVtbl);
+            ((*unknown).release)(self.raw());
+        }
+    }
+}
+
+fn check_hresult(hr: HRESULT) -> io::Result<()> {
+    if hr < 0 {
+        Err(io::Error::from_raw_os_error(hr))
+    } else {
+        Ok(())
+    }
+}
+
+fn read_shortcut(lnk_path: &str) -> io::Result<ShortcutTarget> {
+    unsafe {
+        // COM may already be set up on this thread by the game; only undo our own init
+        let initialized = CoInitializeEx(std::ptr::null(), COINIT_APARTMENTTHREADED as u32) >= 0;
+        let result = (|| {
+            let mut link: *mut c_void = std::ptr::null_mut();
+            check_hresult(CoCreateInstance(
+                &ShellLink,
+                std::ptr::null_mut(),
+                CLSCTX_INPROC_SERVER,
+                &IID_ISHELLLINKW,
+                &mut link,
+            ))?;
+            let link = ComPtr(link as *mut *const IShellLinkWVtbl);
+            let mut file: *mut c_void = std::ptr::null_mut();
+            check_hresult((link.vtbl().base.query_interface)(link.raw(), &IID_IPERSISTFILE, &mut file))?;
+            let file = ComPtr(file as *mut *const IPersistFileVtbl);
+            check_hresult((file.vtbl().load)(file.raw(), to_wide(lnk_path).as_ptr(), STGM_READ))?;
+
+            let mut exe_path = [0u16; 260];
+            check_hresult((link.vtbl().get_path)(
+                link.raw(),
+                exe_path.as_mut_ptr(),
+                exe_path.len() as i32,
+                std::ptr::null_mut(),
+                0,
+            ))?;
+            let mut args = [0u16; 1024];
+            check_hresult((link.vtbl().get_arguments)(link.raw(), args.as_mut_ptr(), args.len() as i32))?;
+            let mut working_dir = [0u16; 260];
+            check_hresult((link.vtbl().get_working_directory)(
+                link.raw(),
+                working_dir.as_mut_ptr(),
+                working_dir.len() as i32,
+            ))?;
+
+            Ok(ShortcutTarget {
+                exe_path: from_wide(&exe_path),
+                args: split_arguments(&from_wide(&args)),
+                working_dir: Some(from_wide(&working_dir)).filter(|dir| !dir.is_empty()),
+            })
+        })();
+        if initialized {
+            CoUninitialize();
+        }
+        result
+    }
+}
+
+// --- Executable Info ---
+#[derive(Clone)]
+struct ExeInfo {
+    modified: Option<SystemTime>,
+    version: Option<String>,
+    architecture: Option<&'static str>,
+}
+
+// Version and architecture for the row tooltip, read again whenever the file changes
+fn exe_info_text(exe_path: &str) -> String {
+    let modified = fs::metadata(exe_path).and_then(|m| m.modified()).ok();
+    let mut cache = EXE_INFO_CACHE.lock().unwrap();
+    let info = match cache.get(exe_path) {
+        Some(info) if info.modified == modified => info.clone(),
+        _ => {
+            let info = ExeInfo {
+                modified,
+                version: read_file_version(exe_path),
+                architecture: read_pe_architecture(exe_path),
+            };
+            cache.insert(exe_path.to_string(), info.clone());
+            info
+        }
+    };
+    format!(
+        "Version: {}\nArchitecture: {}",
+        info.version.as_deref().unwrap_or("unknown"),
+        info.architecture.unwrap_or("unknown")
+    )
+}
+
+// Machine field of the COFF header, found through the offset at 0x3C of the DOS header
+fn read_pe_architecture(exe_path: &str) -> Option<&'static str> {
+    let mut file = fs::File::open(exe_path).ok()?;
+    let mut dos_header = [0u8; 64];
+    file.read_exact(&mut dos_header).ok()?;
+    if &dos_header[..2] != b"MZ" {
+        return None;
+    }
+    let pe_offset = u32::from_le_bytes(dos_header[0x3C..0x40].try_into().ok()?);
+    file.seek(SeekFrom::Start(pe_offset as u64)).ok()?;
+    let mut pe_header = [0u8; 6];
+    file.read_exact(&mut pe_header).ok()?;
+    if &pe_header[..4] != b"PE\0\0" {
+        return None;
+    }
+    match u16::from_le_bytes([pe_header[4], pe_header[5]]) {
+        0x014C => Some("32-bit (x86)"),
+        0x8664 => Some("64-bit (x64)"),
+        0xAA64 => Some("64-bit (ARM64)"),
+        _ => None,
+    }
+}
+
+// File version from the fixed part of the version resource
+fn read_file_version(exe_path: &str) -> Option<String> {
+    let path = to_wide(exe_path);
+    unsafe {
+        let size = GetFileVersionInfoSizeW(path.as_ptr(), std::ptr::null_mut());
+        if size == 0 {
+            return None;
+        }
+        let mut data = vec![0u8; size as usize];
+        if GetFileVersionInfoW(path.as_ptr(), 0, size, data.as_mut_ptr() as *mut c_void) == 0 {
+            return None;
+        }
+
+        let mut info: *mut c_void = std::ptr::null_mut();
+        let mut len = 0u32;
+        if VerQueryValueW(data.as_ptr() as *const c_void, w!("\\"), &mut info, &mut len) == 0
+            || info.is_null()
+            || (len as usize) < std::mem::size_of::<VS_FIXEDFILEINFO>()
+        {
+            return None;
+        }
+        let info = &*(info as *const VS_FIXEDFILEINFO);
+        Some(format!(
+            "{}.{}.{}.{}",
+            info.dwFileVersionMS >> 16,
+            info.dwFileVersionMS & 0xFFFF,
+            info.dwFileVersionLS >> 16,
+            info.dwFileVersionLS & 0xFFFF
+        ))
+    }
+}
+
+// --- Background Tasks & Window Lookup ---
+fn spawn_background_task<F>(task: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    let mut threads = BACKGROUND_THREADS.lock().unwrap();
+    // Checked under the lock join_background_tasks drains with, so nothing slips in after it
+    if UNLOADING.load(Ordering::SeqCst) {
+        return;
+    }
+    threads.retain(|handle| !handle.is_finished());
+    threads.push(thread::spawn(task));
+}
+
+// Sleeps in short slices so unload never waits long on a background task.
+// Returns false if the addon started unloading in the meantime.
+fn sleep_unless_unloading(duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    while !UNLOADING.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(100)));
+    }
+    false
+}
+
+fn join_background_tasks() {
+    let threads: Vec<_> = {
+        let mut threads = BACKGROUND_THREADS.lock().unwrap();
+        UNLOADING.store(true, Ordering::SeqCst);
+        threads.drain(..).collect()
+    };
+    for handle in threads {
+        handle.join().ok();
+    }
+}
+
+// Collects every top-level window belonging to the given process
+fn find_process_windows(pid: u32) -> Vec<HWND> {
+    struct WindowSearch {
+        pid: u32,
+        found: Vec<HWND>,
+    }
+
+    unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam as *mut WindowSearch);
+        let mut window_pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut window_pid);
+        if window_pid == search.pid {
+            search.found.push(hwnd);
+        }
+        TRUE
+    }
+
+    let mut search = WindowSearch {
+        pid,
+        found: Vec::new(),
+    };
+    unsafe {
+        EnumWindows(Some(enum_windows_proc), &mut search as *mut WindowSearch as LPARAM);
+    }
+    search.found
+}
+
+// Finds a visible, unowned top-level window belonging to the given process
+fn find_main_window(pid: u32) -> Option<HWND> {
+    find_process_windows(pid)
+        .into_iter()
+        .find(|&hwnd| unsafe { IsWindowVisible(hwnd) != 0 && GetWindow(hwnd, GW_OWNER).is_null() })
+}
+
+// Posts WM_CLOSE to all of the process's top-level windows; false if it has none
+fn request_process_close(pid: u32) -> bool {
+    let windows = find_process_windows(pid);
+    for &hwnd in &windows {
+        unsafe {
+            PostMessageW(hwnd, WM_CLOSE, 0, 0);
+        }
+    }
+    !windows.is_empty()
+}
+
+fn priority_class(priority: ProcessPriority) -> PROCESS_CREATION_FLAGS {
+    match priority {
+        ProcessPriority::Idle => IDLE_PRIORITY_CLASS,
+        ProcessPriority::BelowNormal => BELOW_NORMAL_PRIORITY_CLASS,
+        ProcessPriority::Normal => NORMAL_PRIORITY_CLASS,
+        ProcessPriority::AboveNormal => ABOVE_NORMAL_PRIORITY_CLASS,
+        ProcessPriority::High => HIGH_PRIORITY_CLASS,
+    }
+}
+
+fn apply_priority(program: &ProgramToLaunch, child: &Child) {
+    let applied = unsafe { SetPriorityClass(child.as_raw_handle(), priority_class(program.priority)) };
+    if applied == 0 {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "Failed to set the priority of {} (PID: {}): {}",
+                program.display_name,
+                child.id(),
+                io::Error::last_os_error()
+            ),
+        );
+    }
+}
+
+// "45 min", "2h 30min"
+fn format_run_time(secs: u64) -> String {
+    let mins = secs / 60;
+    match (mins / 60, mins % 60) {
+        (0, _) => format!("{} min", mins.max(1)),
+        (hours, 0) => format!("{}h", hours),
+        (hours, mins) => format!("{}h {}min", hours, mins),
+    }
+}
+
+fn logical_core_count() -> u32 {
+    *LOGICAL_CORES
+}
+
+fn query_logical_core_count() -> u32 {
+    // Spelled out, SYSTEM_INFO on its own is the process snapshot
+    let mut info: windows_sys::Win32::System::SystemInformation::SYSTEM_INFO = unsafe { std::mem::zeroed() };
+    unsafe { GetSystemInfo(&mut info) };
+    info.dwNumberOfProcessors.clamp(1, 64)
+}
+
+// Drops bits for cores this machine doesn't have; None if no core is left
+fn clamp_affinity_mask(mask: u64, cores: u32) -> Option<u64> {
+    let available = if cores >= 64 { u64::MAX } else { (1u64 << cores) - 1 };
+    Some(mask & available).filter(|mask| *mask != 0)
+}
+
+fn apply_affinity(program: &ProgramToLaunch, child: &Child, mask: u64) {
+    let Some(mask) = clamp_affinity_mask(mask, logical_core_count()) else {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "CPU affinity of {} selects no core of this machine, leaving it on all cores",
+                program.display_name
+            ),
+        );
+        return;
+    };
+    let applied = unsafe { SetProcessAffinityMask(child.as_raw_handle(), mask as usize) };
+    if applied == 0 {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "Failed to set the CPU affinity of {} (PID: {}): {}",
+                program.display_name,
+                child.id(),
+                io::Error::last_os_error()
+            ),
+        );
+    }
+}
+
+// std::process::Command can't set STARTUPINFO.wShowWindow, so wait for the
+// program's main window to appear and adjust it instead
+fn apply_window_state(pid: u32, state: WindowState) {
+    spawn_background_task(move || {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if let Some(hwnd) = find_main_window(pid) {
+                unsafe {
+                    ShowWindow(hwnd, show_window_command(state));
+                }
+                return;
+            }
+            if !sleep_unless_unloading(Duration::from_millis(100)) {
+                return;
+            }
+        }
+    });
+}
+
+// Holds on to a launched child until it exits, killing it if it misses its ready timeout.
+// A killed attempt is retried like a failed spawn while launch retries are left.
+fn watch_launched_child(program: ProgramToLaunch, mut child: Child, attempt: u32) {
+    spawn_background_task(move || {
+        let name = &program.name;
+        let display_name = &program.display_name;
+        if let Some(timeout) = program.ready_timeout_secs.map(Duration::from_secs) {
+            match wait_until_ready(name, display_name, &mut child, timeout) {
+                ReadyWait::Ready => {}
+                ReadyWait::TimedOut if attempt <= program.launch_retries => {
+                    match program_command(&program, false) {
+                        Ok(command) => {
+                            log::log(
+                                LogLevel::Info,
+                                "SYSTEM",
+                                &format!(
+                                    "Retrying {} (attempt {} of {})",
+                                    display_name,
+                                    attempt + 1,
+                                    program.launch_retries + 1
+                                ),
+                            );
+                            retry_launch(program.clone(), command, attempt + 1);
+                        }
+                        Err(e) => set_launch_error(&program, "Failed to parse command", &e),
+                    }
+                    return;
+                }
+                ReadyWait::TimedOut | ReadyWait::Gone => return,
+            }
+        }
+        // Polled so unload doesn't have to wait for the program to exit
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    record_exit_code(name, status.code());
+                    log::log(
+                        LogLevel::Info,
+                        "SYSTEM",
+                        &format!("{} (PID: {}) exited: {}", display_name, child.id(), status),
+                    );
+                    return;
+                }
+                Ok(None) => {}
+                Err(_) => return,
+            }
+            if !sleep_unless_unloading(Duration::from_millis(500)) {
+                return;
+            }
+        }
+    });
+}
+
+enum ReadyWait {
+    Ready,
+    // Exited on its own, or we are unloading
+    Gone,
+    // Killed for missing the timeout
+    TimedOut,
+}
+
+fn wait_until_ready(name: &str, display_name: &str, child: &mut Child, timeout: Duration) -> ReadyWait {
+    let pid = child.id();
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            record_exit_code(name, status.code());
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!("{} (PID: {}) exited before becoming ready: {}", display_name, pid, status),
+            );
+            record_event(display_name, format!("Exited before becoming ready (PID: {}): {}", pid, status));
+            return ReadyWait::Gone;
+        }
+        if find_main_window(pid).is_some() {
+            return ReadyWait::Ready;
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        if !sleep_unless_unloading(Duration::from_millis(250)) {
+            return ReadyWait::Gone;
+        }
+    }
+
+    log::log(
+        LogLevel::Warning,
+        "SYSTEM",
+        &format!(
+            "{} (PID: {}) did not become ready within {}s, killing it.",
+            display_name,
+            pid,
+            timeout.as_secs()
+        ),
+    );
+    match child.kill() {
+        Ok(()) => record_event(display_name, format!("Killed, not ready in time (PID: {})", pid)),
+        Err(e) => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Failed to kill hung process {}: {}", pid, e),
+            );
+            record_event(display_name, format!("Failed to kill (PID: {}): {}", pid, e));
+        }
+    }
+    if let Ok(status) = child.wait() {
+        record_exit_code(name, status.code());
+    }
+    ReadyWait::TimedOut
+}
+
+fn record_exit_code(name: &str, code: Option<i32>) {
+    EXIT_CODES.lock().unwrap().insert(name.to_string(), code);
+}
+
+// Logs each line the program writes to `pipe`. Polls instead of blocking in read() so
+// unload can join the thread; ends once the child and anything sharing its pipe exit.
+fn forward_program_output<R>(display_name: String, is_stderr: bool, mut pipe: R)
+where
+    R: Read + AsRawHandle + Send + 'static,
+{
+    spawn_background_task(move || {
+        let mut buffer = [0u8; 4096];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            let mut available = 0u32;
+            let open = unsafe {
+                PeekNamedPipe(
+                    pipe.as_raw_handle(),
+                    std::ptr::null_mut(),
+                    0,
+                    std::ptr::null_mut(),
+                    &mut available,
+                    std::ptr::null_mut(),
+                )
+            } != 0;
+            if !open {
+                break; // Broken pipe: every writer has exited
+            }
+            if available == 0 {
+                if !sleep_unless_unloading(Duration::from_millis(100)) {
+                    return;
+                }
+                continue;
+            }
+
+            let len = (available as usize).min(buffer.len());
+            let read = match pipe.read(&mut buffer[..len]) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            pending.extend_from_slice(&buffer[..read]);
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                log_program_output(&display_name, is_stderr, &line);
+            }
+        }
+        log_program_output(&display_name, is_stderr, &pending);
+    });
+}
+fn log_program_output(display_name: &str, is_stderr: bool, line: &[u8]) {
+    // Console programs often write in the OEM code page, so don't insist on UTF-8
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end();
+    if !line.is_empty() {
+        let level = if is_stderr { LogLevel::Warning } else { LogLevel::Info };
+        log::log(level, "SYSTEM", &format!("[{}] {}", display_name, line));
+    }
+}
+
+// --- Keep Alive ---
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+const KEEP_ALIVE_MAX_RESTARTS_PER_MINUTE: usize = 3;
+
+#[derive(Default)]
+struct KeepAliveState {
+    was_running: bool,
+    // Set when we close the program ourselves, cleared when we launch it again
+    paused: bool,
+    restarts: Vec<Instant>,
+}
+
+fn set_keep_alive_paused(program_name: &str, paused: bool) {
+    let mut states = KEEP_ALIVE.lock().unwrap();
+    let state = states.entry(program_name.to_string()).or_default();
+    state.paused = paused;
+    state.was_running = false;
+}
+
+// Records the latest running state and decides whether the program crashed and
+// should be relaunched, giving up after too many restarts in a minute
+fn keep_alive_should_relaunch(program: &ProgramToLaunch, running: bool) -> bool {
+    let mut states = KEEP_ALIVE.lock().unwrap();
+    let state = states.entry(program.name.clone()).or_default();
+    if state.paused {
+        return false;
+    }
+    if running {
+        state.was_running = true;
+        return false;
+    }
+    if !state.was_running {
+        return false;
+    }
+
+    state.was_running = false;
+    state.restarts.retain(|at| at.elapsed() < Duration::from_secs(60));
+    if state.restarts.len() >= KEEP_ALIVE_MAX_RESTARTS_PER_MINUTE {
+        state.paused = true;
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "{} exited {} times within a minute, no longer relaunching it",
+                program.display_name,
+                state.restarts.len() + 1
+            ),
+        );
+        return false;
+    }
+    state.restarts.push(Instant::now());
+    log::log(
+        LogLevel::Warning,
+        "SYSTEM",
+        &format!("{} exited unexpectedly, relaunching", program.display_name),
+    );
+    true
+}
+
+fn spawn_keep_alive_watcher() {
+    KEEP_ALIVE_ACTIVE.store(true, Ordering::SeqCst);
+    spawn_background_task(|| {
+        while sleep_unless_unloading(KEEP_ALIVE_INTERVAL) && KEEP_ALIVE_ACTIVE.load(Ordering::SeqCst) {
+            let programs: Vec<ProgramToLaunch> = CONFIG
+                .lock()
+                .unwrap()
+                .programs_to_launch
+                .iter()
+                .filter(|p| p.keep_alive && p.enabled && get_document_path(&p.path).is_none())
+                .cloned()
+                .collect();
+            for program in programs {
+                let Some(process_name) = get_program_process_name(&program) else {
+                    continue;
+                };
+                let running = is_process_running(&process_name);
+                if keep_alive_should_relaunch(&program, running) {
+                    force_launch_process(&program);
+                }
+            }
+        }
+    });
+}
+
+// --- Memory Watch ---
+const MEMORY_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+// Whether a program is over its limit now, given whether it was last time. It has to drop
+// below 90% of the limit before it counts as back under, so hovering at the limit doesn't
+// warn on every check.
+fn memory_over_limit(was_over: bool, memory_mb: u64, limit_mb: u64) -> bool {
+    if was_over {
+        memory_mb * 10 >= limit_mb * 9
+    } else {
+        memory_mb > limit_mb
+    }
+}
+
+fn spawn_memory_watcher() {
+    spawn_background_task(|| {
+        while sleep_unless_unloading(MEMORY_WATCH_INTERVAL) {
+            // process_usage reads what the last scan collected, so make sure there was a recent one
+            with_processes(process_scan_ttl(), |_| ());
+            let programs: Vec<ProgramToLaunch> = CONFIG
+                .lock()
+                .unwrap()
+                .programs_to_launch
+                .iter()
+                .filter(|p| p.memory_warn_mb.is_some() && p.enabled && get_document_path(&p.path).is_none())
+                .cloned()
+                .collect();
+            for program in programs {
+                check_memory_limit(&program);
+            }
+        }
+    });
+}
+
+fn check_memory_limit(program: &ProgramToLaunch) {
+    let Some(limit_mb) = program.memory_warn_mb else {
+        return;
+    };
+    let Some(process_name) = get_program_process_name(program) else {
+        return;
+    };
+    let memory_mb = process_usage(&program.name, &process_name)
+        .map_or(0, |(_, memory)| memory / (1024 * 1024));
+    let crossed = {
+        let mut exceeded = MEMORY_EXCEEDED.lock().unwrap();
+        let was_over = exceeded.contains(&program.name);
+        let over = memory_over_limit(was_over, memory_mb, limit_mb);
+        if over {
+            exceeded.insert(program.name.clone());
+        } else {
+            exceeded.remove(&program.name);
+        }
+        over && !was_over
+    };
+    if !crossed {
+        return;
+    }
+    log::log(
+        LogLevel::Warning,
+        "SYSTEM",
+        &format!(
+            "{} is using {} MB of memory, over its {} MB limit",
+            program.display_name, memory_mb, limit_mb
+        ),
+    );
+    record_event(&program.display_name, format!("Over memory limit: {} MB of {} MB", memory_mb, limit_mb));
+    if program.kill_on_memory_exceed {
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Closing {} for going over its memory limit", program.display_name),
+        );
+        cleanup_processes(&KillTarget::for_program(program).into_iter().collect::<Vec<_>>());
+        MEMORY_EXCEEDED.lock().unwrap().remove(&program.name);
+    }
+}
+
+// --- MumbleLink ---
+const MAP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const LOADING_TICK_STALL: Duration = Duration::from_millis(500);
+
+// Layout of the "MumbleLink" shared memory the game writes every frame
+#[repr(C)]
+struct LinkedMem {
+    ui_version: u32,
+    ui_tick: u32,
+    avatar_position: [f32; 3],
+    avatar_front: [f32; 3],
+    avatar_top: [f32; 3],
+    name: [u16; 256],
+    camera_position: [f32; 3],
+    camera_front: [f32; 3],
+    camera_top: [f32; 3],
+    identity: [u16; 256],
+    context_len: u32,
+    context: [u8; 256],
+    description: [u16; 2048],
+}
+
+// Start of the GW2-specific `context` block, up to the fields we use
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct MumbleContext {
+    server_address: [u8; 28],
+    map_id: u32,
+    map_type: u32,
+}
+
+// Read-only view of the shared memory, kept open until unload
+struct MumbleLinkView {
+    mapping: HANDLE,
+    view: MEMORY_MAPPED_VIEW_ADDRESS,
+}
+
+// Only touched while MUMBLE_LINK is locked
+unsafe impl Send for MumbleLinkView {}
+
+#[derive(Clone, Copy, PartialEq)]
+struct MapInfo {
+    map_id: u32,
+    map_type: u32,
+    // Advances every frame the game renders, stalls during loading screens
+    ui_tick: u32,
+}
+
+// Last ui_tick seen and when it last changed
+struct TickSample {
+    ui_tick: u32,
+    changed_at: Instant,
+}
+
+fn open_mumble_link() -> Option<MumbleLinkView> {
+    let name = to_wide("MumbleLink");
+    unsafe {
+        let mapping = OpenFileMappingW(FILE_MAP_READ, 0, name.as_ptr());
+        if mapping.is_null() {
+            return None;
+        }
+        let view = MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, std::mem::size_of::<LinkedMem>());
+        if view.Value.is_null() {
+            CloseHandle(mapping);
+            return None;
+        }
+        Some(MumbleLinkView { mapping, view })
+    }
+}
+
+fn close_mumble_link() {
+    if let Some(link) = MUMBLE_LINK.lock().unwrap().take() {
+        unsafe {
+            UnmapViewOfFile(link.view);
+            CloseHandle(link.mapping);
+        }
+    }
+}
+
+// Current map, or None while the game hasn't filled in MumbleLink (e.g. character select)
+fn current_map() -> Option<MapInfo> {
+    let mut link = MUMBLE_LINK.lock().unwrap();
+    if link.is_none() {
+        *link = open_mumble_link();
+    }
+    let mem = link.as_ref()?.view.Value as *const LinkedMem;
+    unsafe {
+        // The game writes concurrently, so read the fields rather than borrowing them
+        let ui_tick = std::ptr::read_volatile(std::ptr::addr_of!((*mem).ui_tick));
+        let context_len = std::ptr::read_volatile(std::ptr::addr_of!((*mem).context_len));
+        if ui_tick == 0 || (context_len as usize) < std::mem::size_of::<MumbleContext>() {
+            return None;
+        }
+        let context: MumbleContext = std::ptr::read_volatile(std::ptr::addr_of!((*mem).context) as *const MumbleContext);
+        Some(MapInfo {
+            map_id: context.map_id,
+            map_type: context.map_type,
+            ui_tick,
+        })
+    }
+}
+
+// True while the game shows a loading screen (or character select): MumbleLink is empty,
+// the map is 0, or ui_tick hasn't advanced for LOADING_TICK_STALL
+fn is_loading_screen() -> bool {
+    let Some(map) = current_map().filter(|map| map.map_id != 0) else {
+        return true;
+    };
+    let mut last_tick = LAST_TICK.lock().unwrap();
+    match last_tick.as_ref() {
+        Some(sample) if sample.ui_tick == map.ui_tick => sample.changed_at.elapsed() >= LOADING_TICK_STALL,
+        _ => {
+            *last_tick = Some(TickSample {
+                ui_tick: map.ui_tick,
+                changed_at: Instant::now(),
+            });
+            false
+        }
+    }
+}
+
+// Holds a launch back until the loading screen is over, if the user opted in
+fn defer_if_loading(program: &ProgramToLaunch) -> bool {
+    if !CONFIG.lock().unwrap().defer_launches_while_loading || !is_loading_screen() {
+        return false;
+    }
+    let mut deferred = DEFERRED_LAUNCHES.lock().unwrap();
+    if !deferred.iter().any(|p| p.name == program.name) {
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Loading screen, launching {} once it's over", program.display_name),
+        );
+        deferred.push(program.clone());
+    }
+    true
+}
+
+fn flush_deferred_launches() {
+    if DEFERRED_LAUNCHES.lock().unwrap().is_empty() || is_loading_screen() {
+        return;
+    }
+    let deferred = std::mem::take(&mut *DEFERRED_LAUNCHES.lock().unwrap());
+    for program in &deferred {
+        launch_process(program);
+    }
+}
+
+// Map types as documented for the MumbleLink context
+fn game_mode_of_map_type(map_type: u32) -> Option<GameMode> {
+    match map_type {
+        2 | 3 | 6 | 8 => Some(GameMode::PvP),
+        9..=15 | 18 => Some(GameMode::WvW),
+        4 | 5 | 7 | 16 | 17 => Some(GameMode::PvE),
+        // Redirect and character creation aren't a mode
+        _ => None,
+    }
+}
+fn current_game_mode() -> Option<GameMode> {
+    current_map().and_then(|map| game_mode_of_map_type(map.map_type))
+}
+
+// Fires map triggers on transitions only, never again while the character stays on the map
+fn spawn_map_watcher() {
+    spawn_background_task(|| {
+        // Whatever map we're on at load was handled by the load launches
+        let mut previous: Option<MapInfo> = current_map();
+        while sleep_unless_unloading(MAP_POLL_INTERVAL) {
+            flush_deferred_launches();
+            let current = current_map();
+            if current.map(|m| m.map_id) == previous.map(|m| m.map_id) {
+                continue;
+            }
+            if let Some(map) = previous {
+                close_programs_for_map_leave(map.map_id);
+            }
+            let previous_mode = previous.and_then(|m| game_mode_of_map_type(m.map_type));
+            let current_mode = current.and_then(|m| game_mode_of_map_type(m.map_type));
+            if previous_mode != current_mode {
+                apply_game_mode_change(previous_mode, current_mode);
+            }
+            if let Some(map) = current {
+                log::log(
+                    LogLevel::Debug,
+                    "SYSTEM",
+                    &format!("Entered map {} (type {})", map.map_id, map.map_type),
+                );
+                let entering: Vec<ProgramToLaunch> = CONFIG
+                    .lock()
+                    .unwrap()
+                    .programs_to_launch
+                    .iter()
+                    .filter(|p| p.enabled && map_enter_trigger(p) == Some(map.map_id))
+                    .cloned()
+                    .collect();
+                for program in &entering {
+                    log::log(
+                        LogLevel::Info,
+                        "SYSTEM",
+                        &format!("Entered map {}, launching {}", map.map_id, program.display_name),
+                    );
+                    launch_process(program);
+                }
+            }
+            previous = current;
+        }
+    });
+}
+
+// Closes programs bound to the mode that was left and starts the OnAddonLoad programs
+// that were waiting for the new one
+fn apply_game_mode_change(previous: Option<GameMode>, current: Option<GameMode>) {
+    let programs: Vec<ProgramToLaunch> = CONFIG
+        .lock()
+        .unwrap()
+        .programs_to_launch
+        .iter()
+        .filter(|p| p.enabled && p.active_in != GameMode::Any && get_document_path(&p.path).is_none())
+        .cloned()
+        .collect();
+
+    let targets: Vec<KillTarget> = programs
+        .iter()
+        .filter(|p| p.close_outside_mode && previous == Some(p.active_in))
+        .filter_map(|program| {
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!("Left its game mode, closing {}", program.display_name),
+            );
+            set_keep_alive_paused(&program.name, true);
+            KillTarget::for_program(program)
+        })
+        .collect();
+    if !targets.is_empty() {
+        cleanup_processes(&targets);
+    }
+
+    for program in programs
+        .iter()
+        .filter(|p| current == Some(p.active_in) && p.triggers.contains(&LaunchTrigger::OnAddonLoad))
+    {
+        launch_process(program);
+    }
+}
+
+fn close_programs_for_map_leave(map_id: u32) {
+    let leaving: Vec<ProgramToLaunch> = CONFIG
+        .lock()
+        .unwrap()
+        .programs_to_launch
+        .iter()
+        .filter(|p| map_leave_trigger(p) == Some(map_id) && get_document_path(&p.path).is_none())
+        .cloned()
+        .collect();
+    let targets: Vec<KillTarget> = leaving
+        .iter()
+        .filter_map(|program| {
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!("Left map {}, closing {}", map_id, program.display_name),
+            );
+            set_keep_alive_paused(&program.name, true);
+            KillTarget::for_program(program)
+        })
+        .collect();
+    // Already on a background thread; cleanup_processes skips protected processes
+    if !targets.is_empty() {
+        cleanup_processes(&targets);
+    }
+}
+
+// --- Sensitive Arguments ---
+const ENCRYPTED_ARG_PREFIX: &str = "dpapi:";
+const MASKED_ARG: &str = "********";
+
+// Encrypts with DPAPI, so only the same Windows user can decrypt it again
+fn dpapi_protect(data: &[u8]) -> Option<Vec<u8>> {
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: data.len() as u32,
+        pbData: data.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB {
+        cbData: 0,
+        pbData: std::ptr::null_mut(),
+    };
+    let ok = unsafe {
+        CryptProtectData(
+            &input,
+            std::ptr::null(),
+            std::ptr::null(),
+            std::ptr::null(),
+            std::ptr::null(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    };
+    take_crypt_blob(ok, output)
+}
+
+fn dpapi_unprotect(data: &[u8]) -> Option<Vec<u8>> {
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: data.len() as u32,
+        pbData: data.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB {
+        cbData: 0,
+        pbData: std::ptr::null_mut(),
+    };
+    let ok = unsafe {
+        CryptUnprotectData(
+            &input,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            std::ptr::null(),
+            std::ptr::null(),
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+    };
+    take_crypt_blob(ok, output)
+}
+
+// Copies a blob allocated by DPAPI and frees it
+fn take_crypt_blob(ok: BOOL, blob: CRYPT_INTEGER_BLOB) -> Option<Vec<u8>> {
+    if ok == 0 || blob.pbData.is_null() {
+        return None;
+    }
+    let data = unsafe { std::slice::from_raw_parts(blob.pbData, blob.cbData as usize).to_vec() };
+    unsafe { LocalFree(blob.pbData as *mut c_void) };
+    Some(data)
+}
+
+fn encrypt_sensitive_args(config: &mut Config) {
+    for prog in &mut config.programs_to_launch {
+        for &index in &prog.sensitive_args {
+            let Some(arg) = prog.args.get_mut(index) else {
+                continue;
+            };
+            match dpapi_protect(arg.as_bytes()) {
+                Some(encrypted) => *arg = format!("{}{}", ENCRYPTED_ARG_PREFIX, BASE64.encode(encrypted)),
+                None => {
+                    // Never fall back to writing the plain value
+                    log::log(
+                        LogLevel::Warning,
+                        "SYSTEM",
+                        &format!("Failed to encrypt an argument of {}, it is not saved", prog.display_name),
+                    );
+                    arg.clear();
+                }
+            }
+        }
+    }
+}
+
+fn decrypt_sensitive_args(config: &mut Config) {
+    for prog in &mut config.programs_to_launch {
+        for &index in &prog.sensitive_args {
+            let Some(arg) = prog.args.get_mut(index) else {
+                continue;
+            };
+            let Some(encoded) = arg.strip_prefix(ENCRYPTED_ARG_PREFIX) else {
+                continue;
+            };
+            match BASE64.decode(encoded).ok().and_then(|data| dpapi_unprotect(&data)) {
+                Some(plain) => *arg = String::from_utf8_lossy(&plain).to_string(),
+                None => {
+                    log::log(
+                        LogLevel::Warning,
+                        "SYSTEM",
+                        &format!(
+                            "Could not decrypt an argument of {}, it was saved by another Windows user. Enter it again.",
+                            prog.display_name
+                        ),
+                    );
+                    arg.clear();
+                }
+            }
+        }
+    }
+}
+
+// The combined command as shown in the UI, logs and history, sensitive arguments masked
+fn masked_command(prog: &ProgramToLaunch) -> String {
+    let args: Vec<String> = prog
+        .args
+        .iter()
+        .enumerate()
+        .map(|(index, arg)| {
+            if prog.sensitive_args.contains(&index) {
+                MASKED_ARG.to_string()
+            } else {
+                arg.clone()
+            }
+        })
+        .collect();
+    format!("{} {}", quote_argument(&prog.exe_path), join_arguments(&args))
+        .trim_end()
+        .to_string()
+}
+
+// --- History ---
+const HISTORY_LOG_MAX_BYTES: u64 = 1024 * 1024;
+const HISTORY_MAX_EVENTS: usize = 200;
+// A burst of launches or kills is written to history.ron once, this long after the first
+const HISTORY_SAVE_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Clone)]
+struct HistoryEvent {
+    timestamp: String,
+    program: String,
+    outcome: String,
+}
+
+fn get_history_path() -> PathBuf {
+    get_addon_dir(env!("CARGO_PKG_NAME"))
+        .expect("Addon directory should exist")
+        .join("history.ron")
+}
+
+fn load_history() {
+    let history = match fs::read_to_string(get_history_path()) {
+        Ok(content) => ron::from_str::<VecDeque<HistoryEvent>>(&content).unwrap_or_else(|e| {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Failed to parse history, starting a new one: {}", e),
+            );
+            VecDeque::new()
+        }),
+        Err(_) => VecDeque::new(),
+    };
+    *HISTORY.lock().unwrap() = history;
+}
+
+fn save_history(history: &VecDeque<HistoryEvent>) {
+    let result = ron::ser::to_string_pretty(history, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())
+        .and_then(|serialized| fs::write(get_history_path(), serialized).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Failed to save history: {}", e),
+        );
+    }
+}
+
+// Adds a launch or kill to the History panel and, if enabled, to history.log
+fn record_event(program: &str, outcome: String) {
+    history_log(&format!("{}: {}", program, outcome));
+
+    let mut history = HISTORY.lock().unwrap();
+    history.push_back(HistoryEvent {
+        timestamp: local_timestamp(),
+        program: program.to_string(),
+        outcome,
+    });
+    while history.len() > HISTORY_MAX_EVENTS {
+        history.pop_front();
+    }
+    drop(history);
+    schedule_history_save();
+}
+
+// Writes history.ron HISTORY_SAVE_DELAY from now, together with anything else that changes
+// until then; unload flushes whatever is still pending
+fn schedule_history_save() {
+    if !HISTORY_SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        spawn_background_task(|| {
+            sleep_unless_unloading(HISTORY_SAVE_DELAY);
+            flush_history();
+        });
+    }
+}
+
+// Writes history.ron if events were recorded since the last write
+fn flush_history() {
+    if HISTORY_SAVE_PENDING.swap(false, Ordering::SeqCst) {
+        save_history(&HISTORY.lock().unwrap());
+    }
+}
+
+fn get_history_log_path() -> PathBuf {
+    get_addon_dir(env!("CARGO_PKG_NAME"))
+        .expect("Addon directory should exist")
+        .join("history.log")
+}
+
+fn local_timestamp() -> String {
+    let mut time: SYSTEMTIME = unsafe { std::mem::zeroed() };
+    unsafe { GetLocalTime(&mut time) };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        time.wYear, time.wMonth, time.wDay, time.wHour, time.wMinute, time.wSecond
+    )
+}
+
+// Appends a timestamped line to history.log when file logging is on. Once the file
+// reaches HISTORY_LOG_MAX_BYTES it becomes history.log.1, replacing the older backup.
+fn history_log(message: &str) {
+    if !FILE_LOGGING.load(Ordering::Relaxed) {
+        return;
+    }
+    let _guard = HISTORY_LOG_LOCK.lock().unwrap();
+    let path = get_history_log_path();
+
+    if fs::metadata(&path).is_ok_and(|m| m.len() >= HISTORY_LOG_MAX_BYTES) {
+        if let Err(e) = fs::rename(&path, path.with_extension("log.1")) {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Failed to rotate history log: {}", e),
+            );
+        }
+    }
+
+    let line = format!("{} {}\n", local_timestamp(), message);
+    let result = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+    if let Err(e) = result {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Failed to write history log: {}", e),
+        );
+    }
+}
+
+// --- Quick Access & Icon Management ---
+const ICON_SIZE: u32 = 32;
+
+// Single-color icon for the launch-all and kill-all tiles. `shape` gets x, y and the
+// icon size and says whether the pixel is part of the glyph.
+fn create_glyph_icon(path: &Path, color: [u8; 4], shape: fn(i32, i32, i32) -> bool) {
+    let img = image::RgbaImage::from_fn(ICON_SIZE, ICON_SIZE, |x, y| {
+        if shape(x as i32, y as i32, ICON_SIZE as i32) {
+            image::Rgba(color)
+        } else {
+            image::Rgba([0, 0, 0, 0])
+        }
+    });
+    img.save_with_format(path, image::ImageFormat::Png).ok();
+}
+// Green "play" triangle
+fn launch_all_glyph(x: i32, y: i32, size: i32) -> bool {
+    x >= size / 4 && (y - size / 2).abs() * 3 <= (size * 3 / 4 - x) * 2
+}
+// Red "stop" square
+fn kill_all_glyph(x: i32, y: i32, size: i32) -> bool {
+    (size / 4..size * 3 / 4).contains(&x) && (size / 4..size * 3 / 4).contains(&y)
+}
+// Quick Access tile for one of the addon-wide keybinds
+fn setup_global_quick_access(keybind: &str, tooltip: &str, color: [u8; 4], shape: fn(i32, i32, i32) -> bool) {
+    let Some(addon_dir) = get_addon_dir(env!("CARGO_PKG_NAME")) else {
+        return;
+    };
+    let icons_dir = addon_dir.join("icons");
+    fs::create_dir_all(&icons_dir).ok();
+    let icon_path = icons_dir.join(format!("__{}.png", keybind.to_lowercase()));
+    if !icon_path.exists() {
+        create_glyph_icon(&icon_path, color, shape);
+    }
+    let qa_item_id = format!("QA_ITEM_{}", keybind);
+    let qa_tex_id = format!("QA_TEX_{}", keybind);
+    get_texture_or_create_from_file(&qa_tex_id, &icon_path);
+
+    remove_quick_access(&qa_item_id);
+    add_quick_access(&qa_item_id, &qa_tex_id, &qa_tex_id, keybind, tooltip).revert_on_unload();
+}
+fn create_placeholder_icon(path: &Path) {
+    image::RgbaImage::new(ICON_SIZE, ICON_SIZE)
+        .save_with_format(path, image::ImageFormat::Png)
+        .ok();
+}
+// Every icon ends up as a 32x32 PNG so the texture loader always gets the same thing
+fn save_icon_png(img: image::DynamicImage, save_path: &Path) -> Result<(), String> {
+    img.resize_exact(ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Lanczos3)
+        .save_with_format(save_path, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())
+}
+fn extract_and_save_icon(exe_path: &str, save_path: &Path) -> Result<(), String> {
+    let base64_str = get_icon_base64_by_path(exe_path).map_err(|e| e.to_string())?;
+    let image_data = BASE64.decode(base64_str).map_err(|e| e.to_string())?;
+    // Guess the format rather than assuming PNG, the data can also be an ICO
+    let img = image::ImageReader::new(Cursor::new(&image_data))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .decode()
+        .map_err(|e| e.to_string())?;
+    save_icon_png(img, save_path)
+}
+// Converts a user-chosen image into the PNG the texture loader expects.
+// For .ico files the decoder picks the largest frame.
+fn save_custom_icon(source_path: &str, save_path: &Path) -> Result<(), String> {
+    let img = image::open(source_path).map_err(|e| e.to_string())?;
+    save_icon_png(img, save_path)
+}
+// Nexus keeps textures by ID for the whole session, so the ID includes the icon's
+// modification time to pick up an icon that was replaced on disk
+fn icon_texture_id(program_name: &str) -> String {
+    let modified = ICON_CACHE
+        .lock()
+        .unwrap()
+        .get(program_name)
+        .and_then(|path| fs::metadata(path).ok()?.modified().ok())
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+    match modified {
+        Some(time) => format!("QA_TEX_{}_{}", program_name, time.as_millis()),
+        None => format!("QA_TEX_{}", program_name),
+    }
+}
+// Writes the tile icon: the custom icon if set, else the program's own icon, else a placeholder
+fn create_program_icon(program: &ProgramToLaunch, icon_path: &Path) {
+    if let Some(icon_override) = &program.icon_override {
+        match save_custom_icon(icon_override, icon_path) {
+            Ok(()) => return,
+            Err(e) => {
+                log::log(
+                    LogLevel::Warning,
+                    "SYSTEM",
+                    &format!(
+                        "Could not load custom icon {} for {}: {}. Using the program's icon.",
+                        icon_override, program.display_name, e
+                    ),
+                );
+            }
+        }
+    }
+
+    // URLs have no file to take an icon from
+    if is_url(&program.path) {
+        create_placeholder_icon(icon_path);
+        return;
+    }
+
+    // For documents this resolves to the file itself, which yields its file-association icon
+    if let Some((exe_path, _)) = get_program_command(program) {
+        if let Err(e) = extract_and_save_icon(&exe_path, icon_path) {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!(
+                    "Could not extract icon for {}: {}. Using placeholder.",
+                    program.display_name, e
+                ),
+            );
+            create_placeholder_icon(icon_path);
+        }
+    } else {
+        create_placeholder_icon(icon_path);
+    }
+}
+// True when the icon's source file was modified after the cached PNG was written,
+// e.g. because the program was updated
+fn is_icon_outdated(program: &ProgramToLaunch, icon_path: &Path) -> bool {
+    let source_path = match &program.icon_override {
+        Some(icon_override) => icon_override.clone(),
+        None => match get_program_command(program) {
+            Some((exe_path, _)) => exe_path,
+            None => return false,
+        },
+    };
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(Path::new(&source_path)), modified(icon_path)) {
+        (Some(source_time), Some(icon_time)) => source_time > icon_time,
+        _ => false,
+    }
+}
+fn setup_quick_access_for_program(program: &ProgramToLaunch) {
+    let addon_dir = match get_addon_dir(env!("CARGO_PKG_NAME")) {
+        Some(dir) => dir,
+        None => return,
+    };
+    let icons_dir = addon_dir.join("icons");
+    fs::create_dir_all(&icons_dir).ok();
+
+    let icon_path = icons_dir.join(format!("{}.png", program.name));
+    if !icon_path.exists() || is_icon_outdated(program, &icon_path) {
+        create_program_icon(program, &icon_path);
+    }
+
+    ICON_CACHE
+        .lock()
+        .unwrap()
+        .insert(program.name.clone(), icon_path.clone());
+    get_texture_or_create_from_file(&icon_texture_id(&program.name), &icon_path);
+
+    if program.enabled && program.show_in_quick_access {
+        let running = program
+            .triggers
+            .contains(&LaunchTrigger::Toggle)
+            .then(|| get_program_process_name(program).is_some_and(|name| is_process_running(&name)));
+        add_program_quick_access(program, running);
+    }
+}
+// (Re-)adds the tile; toggle programs show their running state in the tooltip
+fn add_program_quick_access(program: &ProgramToLaunch, running: Option<bool>) {
+    let qa_item_id = format!("QA_ITEM_{}", program.name);
+    let qa_tex_id = icon_texture_id(&program.name);
+    let tooltip = match running {
+        Some(true) => format!("{} (running)", program.display_name),
+        Some(false) => format!("{} (stopped)", program.display_name),
+        None => program.display_name.clone(),
+    };
+
+    remove_quick_access(&qa_item_id);
+    add_quick_access(
+        &qa_item_id,
+        &qa_tex_id,
+        &qa_tex_id,
+        &format!("LAUNCH_{}", program.name),
+        &tooltip,
+    ).revert_on_unload();
+}
+// Throws away the cached PNG and texture and builds them again from the source
+fn refresh_program_icon(program: &ProgramToLaunch) {
+    teardown_quick_access_for_program(program);
+    setup_quick_access_for_program(program);
+    let icon_ok = ICON_CACHE
+        .lock()
+        .unwrap()
+        .get(&program.name)
+        .is_some_and(|path| path.exists());
+    log::log(
+        if icon_ok { LogLevel::Info } else { LogLevel::Warning },
+        "SYSTEM",
+        &format!(
+            "Icon refresh for {}: {}",
+            program.display_name,
+            if icon_ok { "done" } else { "failed" }
+        ),
+    );
+}
+fn teardown_quick_access_for_program(program: &ProgramToLaunch) {
+    let qa_item_id = format!("QA_ITEM_{}", program.name);
+    remove_quick_access(&qa_item_id);
+
+    if let Some(path) = ICON_CACHE.lock().unwrap().remove(&program.name) {
+        fs::remove_file(path).ok();
+    }
+}
+
+// --- Core Logic ---
+// Not LAUNCH_ALL, which a program named "ALL" would already use
+const LAUNCH_ALL_KEYBIND: &str = "DEPLOY_ALL";
+const KILL_ALL_KEYBIND: &str = "KILL_ALL";
+
+extern "C-unwind" fn keybind_callback(identifier: *const c_char, is_release: bool) {
+    if is_release || identifier.is_null() {
+        return;
+    }
+    let result = panic::catch_unwind(|| {
+        let identifier_cstr = unsafe { CStr::from_ptr(identifier) };
+        if let Ok(id_str) = identifier_cstr.to_str() {
+            if id_str == LAUNCH_ALL_KEYBIND {
+                launch_all_programs();
+            } else if id_str == KILL_ALL_KEYBIND {
+                kill_all_processes();
+            } else if let Some(name) = id_str.strip_prefix("LAUNCH_") {
+                launch_process_by_name(name);
+            }
+        }
+    });
+    if result.is_err() {
+        log::log(
+            LogLevel::Critical,
+            "SYSTEM",
+            "Panic caught in keybind handler!",
+        );
+    }
+}
+fn register_program_keybind(program: &ProgramToLaunch) {
+    register_keybind_with_string(
+        format!("LAUNCH_{}", program.name),
+        keybind_callback,
+        &program.keybind,
+    )
+    .revert_on_unload();
+}
+fn get_config_path() -> PathBuf {
+    get_addon_dir(env!("CARGO_PKG_NAME"))
+        .expect("Addon directory should exist")
+        .join("settings.ron")
+}
+// Returns true if the file was in an older format and has been upgraded in memory
+fn load_config_from_file() -> bool {
+    let path = get_config_path();
+    let mut upgraded = false;
+
+    let loaded_config = match fs::read_to_string(&path) {
+        Ok(content) => {
+            log::log(LogLevel::Info, "SYSTEM", "Loading configuration file...");
+            
+            // First try to load as new format; files without a version field parse as format 1
+            match ron::from_str::<Config>(&content) {
+                Ok(mut config) => {
+                    log::log(
+                        LogLevel::Info,
+                        "SYSTEM",
+                        &format!("Configuration loaded successfully (format {})", config.version),
+                    );
+                    backup_loaded_file(&path);
+                    upgraded = upgrade_config(&mut config);
+                    decrypt_sensitive_args(&mut config);
+                    config
+                }
+                Err(_) => {
+                    // Try legacy format
+                    log::log(LogLevel::Info, "SYSTEM", "Trying legacy configuration format...");
+                    match ron::from_str::<LegacyConfig>(&content) {
+                        Ok(legacy_config) => {
+                            log::log(LogLevel::Info, "SYSTEM", "Legacy configuration loaded, converting to new format");
+                            let mut new_config = Config::from(legacy_config);
+                            upgraded = upgrade_config(&mut new_config);
+                            
+                            // Save the converted config immediately
+                            let config_to_save = new_config.clone();
+                            *CONFIG.lock().unwrap() = new_config.clone();
+                            drop(CONFIG.lock().unwrap()); // Make sure we release the lock
+                            
+                            // Save in new format
+                            let save_path = get_config_path();
+                            if let Some(parent) = save_path.parent() {
+                                fs::create_dir_all(parent).ok();
+                            }
+                            if let Ok(serialized) = ron::ser::to_string_pretty(&config_to_save, ron::ser::PrettyConfig::default()) {
+                                fs::write(save_path, serialized).ok();
+                            }
+                            
+                            new_config
+                        }
+                        Err(e) => {
+                            log::log(
+                                LogLevel::Warning,
+                                "SYSTEM",
+                                &format!("Failed to parse config file as legacy format: {}. Using defaults.", e),
+                            );
+                            
+                            // Backup the corrupted config
+                            backup_corrupted_file(&path);
+                            
+                            Config::default()
+                        }
+                    }
+                }
+            }
+        }
+        Err(_) => {
+            log::log(LogLevel::Info, "SYSTEM", "No configuration file found, using defaults");
+            Config::default()
+        }
+    };
+    
+    *CONFIG.lock().unwrap() = loaded_config;
+    upgraded
+}
+
+const CONFIG_BACKUP_COUNT: usize = 5;
+
+// Keeps a corrupted file as <name>.backup.1, shifting older copies up to .backup.5
+fn backup_corrupted_file(path: &Path) {
+    let backup_path = |n: usize| PathBuf::from(format!("{}.backup.{}", path.display(), n));
+    fs::remove_file(backup_path(CONFIG_BACKUP_COUNT)).ok();
+    for n in (1..CONFIG_BACKUP_COUNT).rev() {
+        fs::rename(backup_path(n), backup_path(n + 1)).ok();
+    }
+    match fs::copy(path, backup_path(1)) {
+        Ok(_) => log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Backed up corrupted file to {}", backup_path(1).display()),
+        ),
+        Err(e) => log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Failed to back up corrupted file {}: {}", path.display(), e),
+        ),
+    }
+}
+
+// Copies a file that loaded fine into backups/ before migrations touch it, keeping the
+// newest CONFIG_BACKUP_COUNT copies. Nothing is written when the newest backup already
+// holds the same content, so unchanged files don't push older backups out on every start.
+fn backup_loaded_file(path: &Path) {
+    let (Some(dir), Some(file_name)) = (get_addon_dir(env!("CARGO_PKG_NAME")), path.file_name()) else {
+        return;
+    };
+    let dir = dir.join("backups");
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::log(LogLevel::Warning, "SYSTEM", &format!("Failed to create backup folder: {}", e));
+        return;
+    }
+    let file_name = file_name.to_string_lossy();
+
+    // Timestamps sort chronologically as text
+    let prefix = format!("{}.", file_name);
+    let list_backups = || {
+        let mut backups: Vec<PathBuf> = fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|p| {
+                        p.file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".bak"))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        backups.sort();
+        backups
+    };
+
+    if let Some(newest) = list_backups().last() {
+        if let (Ok(current), Ok(previous)) = (fs::read(path), fs::read(newest)) {
+            if current == previous {
+                return;
+            }
+        }
+    }
+
+    let stamp = local_timestamp().replace(' ', "_").replace(':', "-");
+    if let Err(e) = fs::copy(path, dir.join(format!("{}.{}.bak", file_name, stamp))) {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Failed to back up {}: {}", file_name, e),
+        );
+        return;
+    }
+
+    let backups = list_backups();
+    let excess = backups.len().saturating_sub(CONFIG_BACKUP_COUNT);
+    for old in &backups[..excess] {
+        fs::remove_file(old).ok();
+    }
+}
+
+// Brings a parsed config up to CONFIG_VERSION, one version step at a time. Returns whether
+// anything was upgraded, so the caller can write the new format back.
+fn upgrade_config(config: &mut Config) -> bool {
+    if config.version > CONFIG_VERSION {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "Configuration was written by a newer version (format {}, this one reads {}), unknown settings are lost",
+                config.version, CONFIG_VERSION
+            ),
+        );
+    }
+    if config.version < 2 {
+        // Fold the old single trigger into the trigger set
+        for prog in &mut config.programs_to_launch {
+            if let Some(trigger) = prog.trigger.take() {
+                prog.triggers.insert(trigger);
+            }
+        }
+    }
+    if config.version < 3 {
+        split_combined_commands(config);
+    }
+    if config.version >= CONFIG_VERSION {
+        return false;
+    }
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Upgraded configuration from format {} to {}", config.version, CONFIG_VERSION),
+    );
+    config.version = CONFIG_VERSION;
+    true
+}
+
+fn get_profiles_path(format: ConfigFormat) -> PathBuf {
+    let file_name = match format {
+        ConfigFormat::Ron => "profiles.ron",
+        ConfigFormat::Json => "profiles.json",
+    };
+    get_addon_dir(env!("CARGO_PKG_NAME"))
+        .expect("Addon directory should exist")
+        .join(file_name)
+}
+
+// The profiles file on disk and its format; the newer one if both exist
+fn existing_profiles_path() -> Option<(PathBuf, ConfigFormat)> {
+    CONFIG_FORMATS
+        .iter()
+        .map(|(format, _)| (get_profiles_path(*format), *format))
+        .filter_map(|(path, format)| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((modified, path, format))
+        })
+        .max_by_key(|(modified, _, _)| *modified)
+        .map(|(_, path, format)| (path, format))
+}
+
+fn parse_profiles(content: &str, format: ConfigFormat) -> Result<ProfileStore, String> {
+    match format {
+        ConfigFormat::Ron => ron::from_str(content).map_err(|e| e.to_string()),
+        ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+    }
+}
+
+fn serialize_profiles(store: &ProfileStore, format: ConfigFormat) -> Result<String, String> {
+    match format {
+        ConfigFormat::Ron => {
+            ron::ser::to_string_pretty(store, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())
+        }
+        ConfigFormat::Json => serde_json::to_string_pretty(store).map_err(|e| e.to_string()),
+    }
+}
+
+// Falls back to settings.ron, which becomes the default profile, when there is
+// no profiles file yet
+// Reads and parses the profiles file without touching CONFIG or PROFILES; None if there is none
+fn read_profiles_file() -> Option<(PathBuf, Result<ProfileStore, String>)> {
+    let (path, format) = existing_profiles_path()?;
+    let store = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| parse_profiles(&content, format))
+        .map(|mut store| {
+            // The file that exists decides, whatever the setting inside says
+            for config in store.profiles.values_mut() {
+                config.config_format = format;
+            }
+            store
+        });
+    Some((path, store))
+}
+
+fn apply_profile_store(mut store: ProfileStore) {
+    let mut upgraded = false;
+    for config in store.profiles.values_mut() {
+        upgraded |= upgrade_config(config);
+        decrypt_sensitive_args(config);
+    }
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Loaded {} profile(s), active: {}", store.profiles.len(), store.active_profile),
+    );
+    *CONFIG.lock().unwrap() = store.profiles.get(&store.active_profile).cloned().unwrap_or_default();
+    *PROFILES.lock().unwrap() = store;
+    // Written back right away so upgrade steps run, and log, only once
+    if upgraded {
+        save_config_to_file();
+    }
+}
+
+// Startup load: an unreadable profiles file is set aside and settings.ron or defaults used instead
+fn load_profiles_from_file() {
+    remember_config_file_mtime();
+    match read_profiles_file() {
+        Some((path, Ok(store))) => {
+            backup_loaded_file(&path);
+            apply_profile_store(store);
+            return;
+        }
+        Some((path, Err(e))) => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Failed to parse profiles file: {}. Falling back to settings.ron.", e),
+            );
+            backup_corrupted_file(&path);
+        }
+        None => {}
+    }
+
+    let upgraded = load_config_from_file();
+    let config = CONFIG.lock().unwrap().clone();
+    *PROFILES.lock().unwrap() = ProfileStore {
+        active_profile: DEFAULT_PROFILE.to_string(),
+        profiles: HashMap::from([(DEFAULT_PROFILE.to_string(), config)]),
+    };
+    // Same as apply_profile_store: the upgrade steps run, and log, only once
+    if upgraded {
+        save_config_to_file();
+    }
+}
+
+// Writes the active config back into its profile and saves all profiles
+fn save_config_to_file() {
+    let format = CONFIG.lock().unwrap().config_format;
+    let path = get_profiles_path(format);
+    if let Some(parent) = path.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            log::log(
+                LogLevel::Critical,
+                "SYSTEM",
+                &format!("Failed to create config directory: {}", e),
+            );
+            return;
+        }
+    }
+    
+    let mut config = {
+        let config = CONFIG.lock().unwrap();
+        // Cleared under the CONFIG lock, so every launch counted so far is in this copy
+        UNSAVED_LAUNCHES.lock().unwrap().clear();
+        config.clone()
+    };
+    config.version = CONFIG_VERSION;
+    let mut profiles = PROFILES.lock().unwrap();
+    let active_profile = profiles.active_profile.clone();
+    profiles.profiles.insert(active_profile, config);
+    // Only the file holds encrypted arguments, memory keeps them usable
+    let mut on_disk = profiles.clone();
+    for config in on_disk.profiles.values_mut() {
+        encrypt_sensitive_args(config);
+    }
+    match serialize_profiles(&on_disk, format) {
+        Ok(serialized) => {
+            if let Err(e) = fs::write(&path, serialized) {
+                log::log(
+                    LogLevel::Critical,
+                    "SYSTEM",
+                    &format!("Failed to write config file: {}", e),
+                );
+            } else {
+                // A file left in the other format would be stale from now on
+                for (other, _) in CONFIG_FORMATS.iter().filter(|(other, _)| *other != format) {
+                    let other_path = get_profiles_path(*other);
+                    if other_path.exists() && fs::remove_file(&other_path).is_ok() {
+                        log::log(
+                            LogLevel::Info,
+                            "SYSTEM",
+                            &format!("Switched settings file to {}", path.display()),
+                        );
+                    }
+                }
+                // Our own writes must not look like a hand edit to the config watcher
+                remember_config_file_mtime();
+                log::log(LogLevel::Info, "SYSTEM", "Configuration saved successfully");
+            }
+        }
+        Err(e) => {
+            log::log(
+                LogLevel::Critical,
+                "SYSTEM",
+                &format!("Failed to serialize config: {}", e),
+            );
+        }
+    }
+}
+
+// Upgrade to format 3 of entries that only have the combined path+args string
+fn split_combined_commands(config: &mut Config) {
+    for prog in config.programs_to_launch.iter_mut().filter(|p| p.exe_path.is_empty()) {
+        if split_program_command(prog) {
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!(
+                    "Split command for {}: exe '{}', {} argument(s)",
+                    prog.display_name,
+                    prog.exe_path,
+                    prog.args.len()
+                ),
+            );
+        } else {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!(
+                    "Could not split command for {}, keeping combined path: {}",
+                    prog.display_name, prog.path
+                ),
+            );
+        }
+    }
+}
+
+// What a validation pass changed, for the summary after "Validate & Clean"
+#[derive(Default, Clone, Copy)]
+struct ValidationSummary {
+    removed: usize,
+    fixed: usize,
+}
+
+fn validate_and_cleanup_config() -> ValidationSummary {
+    let mut config = CONFIG.lock().unwrap();
+    let mut summary = ValidationSummary::default();
+    let mut needs_save = false;
+    let mut used_names = HashSet::new();
+    let mut renamed = Vec::new();
+
+    PROCESS_SCAN_TTL_MS.store(config.process_scan_ttl_ms, Ordering::Relaxed);
+    FILE_LOGGING.store(config.file_logging, Ordering::Relaxed);
+
+    log::log(LogLevel::Info, "SYSTEM", "Validating configuration...");
+
+    // Clean up and validate programs
+    config.programs_to_launch.retain_mut(|prog| {
+        // Validate path exists (basic check), URLs aren't files
+        if let Some((exe_path, _)) = get_program_command(prog).filter(|_| !is_url(&prog.path)) {
+            if !Path::new(&exe_path).exists() {
+                log::log(
+                    LogLevel::Warning,
+                    "SYSTEM",
+                    &format!("Removing program with non-existent path: {}", prog.path),
+                );
+                summary.removed += 1;
+                return false;
+            }
+        }
+        let mut fixed = false;
+
+        // Ensure display_name is set
+        if prog.display_name.is_empty() {
+            if let Some(base_name) = get_program_name_from_command(&prog.path) {
+                prog.display_name = base_name;
+                fixed = true;
+            } else {
+                prog.display_name = prog.name.clone();
+                fixed = true;
+            }
+        }
+
+        // Ensure name is properly sanitized and unique
+        if prog.name.is_empty() {
+            if let Some(base_name) = get_program_name_from_command(&prog.path) {
+                prog.name = sanitize_identifier(&base_name);
+                fixed = true;
+            } else {
+                prog.name = sanitize_identifier(&prog.display_name);
+                fixed = true;
+            }
+        }
+
+        // Ensure uniqueness
+        let base_name = prog.name.clone();
+        let mut final_name = base_name.clone();
+        let mut suffix = 2;
+        
+        while used_names.contains(&final_name) {
+            final_name = format!("{}_{}", base_name, suffix);
+            suffix += 1;
+        }
+        
+        if final_name != prog.name {
+            renamed.push((std::mem::replace(&mut prog.name, final_name), prog.clone()));
+            fixed = true;
+        }
+        
+        used_names.insert(prog.name.clone());
+        if fixed {
+            summary.fixed += 1;
+            needs_save = true;
+        }
+        true
+    });
+
+    if config.programs_to_launch.windows(2).any(|w| !w[0].is_favorite && w[1].is_favorite) {
+        favorites_first(&mut config.programs_to_launch);
+        needs_save = true;
+    }
+
+    refresh_duplicate_paths(&config.programs_to_launch);
+
+    // Old IDs still owned by another program (deduplication) must stay registered
+    let remaining: HashSet<String> = config.programs_to_launch.iter().map(|p| p.name.clone()).collect();
+    drop(config);
+    for (old_name, prog) in &renamed {
+        migrate_program_keybind(old_name, prog, !remaining.contains(old_name));
+    }
+
+    if needs_save {
+        log::log(LogLevel::Info, "SYSTEM", "Configuration updated, saving...");
+        save_config_to_file();
+    } else {
+        log::log(LogLevel::Info, "SYSTEM", "Configuration is valid");
+    }
+    summary
+}
+
+// Registers the keybind and Quick Access tile of a program
+fn setup_program(program: &ProgramToLaunch) {
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Setting up program: {} ({})", program.display_name, program.name),
+    );
+
+    // Quick access tiles invoke the keybind too, so they need it registered
+    if uses_keybind(program) || program.show_in_quick_access {
+        register_program_keybind(program);
+    }
+
+    if program.enabled {
+        setup_quick_access_for_program(program);
+    }
+}
+// Moves the keybind of a program whose internal name changed from `old_name` to its new name
+fn migrate_program_keybind(old_name: &str, program: &ProgramToLaunch, release_old: bool) {
+    if old_name.is_empty() {
+        return;
+    }
+    if release_old {
+        unregister_keybind(format!("LAUNCH_{}", old_name));
+    }
+    if uses_keybind(program) || program.show_in_quick_access {
+        register_program_keybind(program);
+    }
+
+    if program.keybind.is_empty() {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!(
+                "Keybind ID of {} changed from LAUNCH_{} to LAUNCH_{}, rebind it in Nexus",
+                program.display_name, old_name, program.name
+            ),
+        );
+    } else {
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!(
+                "Moved keybind {} of {} from LAUNCH_{} to LAUNCH_{}",
+                program.keybind, program.display_name, old_name, program.name
+            ),
+        );
+    }
+}
+fn teardown_program(program: &ProgramToLaunch) {
+    remove_quick_access(&format!("QA_ITEM_{}", program.name));
+    unregister_keybind(format!("LAUNCH_{}", program.name));
+}
+
+// Saves the current profile, removes its tiles and keybinds, and sets up `name` instead.
+// Auto-launch triggers only fire on addon load, not when switching.
+fn switch_profile(name: &str) {
+    *REMOVED_PROGRAM.lock().unwrap() = None;
+    save_config_to_file();
+
+    let old_config = CONFIG.lock().unwrap().clone();
+    for program in &old_config.programs_to_launch {
+        teardown_program(program);
+    }
+
+    let new_config = {
+        let mut profiles = PROFILES.lock().unwrap();
+        profiles.active_profile = name.to_string();
+        profiles.profiles.entry(name.to_string()).or_default().clone()
+    };
+    *CONFIG.lock().unwrap() = new_config;
+    sync_interpreters();
+    validate_and_cleanup_config();
+
+    let config = CONFIG.lock().unwrap().clone();
+    for program in &config.programs_to_launch {
+        setup_program(program);
+    }
+    save_config_to_file();
+
+    log::log(LogLevel::Info, "SYSTEM", &format!("Switched to profile: {}", name));
+}
+
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+fn config_file_mtime() -> Option<SystemTime> {
+    let (path, _) = existing_profiles_path()?;
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn remember_config_file_mtime() {
+    *CONFIG_FILE_MTIME.lock().unwrap() = config_file_mtime();
+}
+
+// Picks up hand edits of the profiles file. A change is only reloaded once the file has stopped
+// changing for a poll interval, so an editor saving in several steps causes one reload.
+fn spawn_config_watcher() {
+    spawn_background_task(|| {
+        let mut pending: Option<SystemTime> = None;
+        while sleep_unless_unloading(CONFIG_WATCH_INTERVAL) {
+            let modified = config_file_mtime();
+            if modified.is_none() || modified == *CONFIG_FILE_MTIME.lock().unwrap() {
+                pending = None;
+                continue;
+            }
+            if pending != modified {
+                pending = modified;
+                continue;
+            }
+            pending = None;
+            // Reloading sets up keybinds and Quick Access, which belongs on the render thread
+            CONFIG_RELOAD_PENDING.store(true, Ordering::SeqCst);
+        }
+    });
+}
+
+// Render callback that applies a reload the watcher asked for
+fn apply_pending_config_reload(_ui: &Ui) {
+    if CONFIG_RELOAD_PENDING.swap(false, Ordering::SeqCst) {
+        reload_config_from_disk();
+    }
+}
+
+// Like a profile switch, but to whatever is on disk now; auto-launch triggers don't fire.
+// A file that can't be read or parsed, e.g. one an editor is halfway through writing, leaves
+// everything as it is; the next change to the file is picked up again.
+fn reload_config_from_disk() {
+    remember_config_file_mtime();
+    let store = match read_profiles_file() {
+        Some((_, Ok(store))) => store,
+        Some((path, Err(e))) => {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!(
+                    "Not reloading {}, it could not be read: {}. Keeping the current settings.",
+                    path.display(),
+                    e
+                ),
+            );
+            return;
+        }
+        None => return,
+    };
+
+    *REMOVED_PROGRAM.lock().unwrap() = None;
+    log::log(LogLevel::Info, "SYSTEM", "Profiles file changed on disk, reloading");
+    let old_config = CONFIG.lock().unwrap().clone();
+    for program in &old_config.programs_to_launch {
+        teardown_program(program);
+    }
+
+    // Launches counted since the last save aren't in the file, add them to what was read
+    // if it is still the same profile
+    let unsaved = UNSAVED_LAUNCHES.lock().unwrap().clone();
+    let same_profile = PROFILES.lock().unwrap().active_profile == store.active_profile;
+    apply_profile_store(store);
+    if same_profile {
+        let mut config = CONFIG.lock().unwrap();
+        for prog in &mut config.programs_to_launch {
+            prog.launch_count += unsaved.get(&prog.name).copied().unwrap_or(0);
+        }
+        *UNSAVED_LAUNCHES.lock().unwrap() = unsaved;
+    }
+    sync_interpreters();
+    validate_and_cleanup_config();
+
+    let config = CONFIG.lock().unwrap().clone();
+    for program in &config.programs_to_launch {
+        setup_program(program);
+    }
+}
+
+// Programs launched during the current game session, kept in the temp folder so it
+// survives addon reloads but not a game restart
+#[derive(Serialize, Deserialize, Default)]
+struct SessionMarker {
+    session: String,
+    launched: HashSet<String>,
+}
+
+fn get_session_marker_path() -> PathBuf {
+    std::env::temp_dir().join(format!("{}_session.ron", env!("CARGO_PKG_NAME")))
+}
+
+// The game's PID plus its start time, so a reused PID doesn't count as the same session
+fn current_session_id() -> String {
+    let pid = std::process::id();
+    let started = with_processes(process_scan_ttl(), |sys| sys.process(Pid::from_u32(pid)).map(|p| p.start_time()));
+    format!("{}:{}", pid, started.unwrap_or(0))
+}
+
+fn load_session_marker() -> SessionMarker {
+    let session = current_session_id();
+    fs::read_to_string(get_session_marker_path())
+        .ok()
+        .and_then(|content| ron::from_str::<SessionMarker>(&content).ok())
+        .filter(|marker| marker.session == session)
+        .unwrap_or(SessionMarker {
+            session,
+            launched: HashSet::new(),
+        })
+}
+
+fn mark_launched_this_session(program_name: &str) {
+    let mut marker = load_session_marker();
+    if !marker.launched.insert(program_name.to_string()) {
+        return;
+    }
+    let result = ron::to_string(&marker)
+        .map_err(|e| e.to_string())
+        .and_then(|serialized| fs::write(get_session_marker_path(), serialized).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::log(
+            LogLevel::Warning,
+            "SYSTEM",
+            &format!("Failed to write session marker: {}", e),
+        );
+    }
+}
+
+fn load() {
+    UNLOADING.store(false, Ordering::SeqCst);
+
+    // Neither a profiles file nor the older settings.ron: this is the first run
+    let first_run = existing_profiles_path().is_none() && !get_config_path().exists();
+
+    // Load config with backwards compatibility
+    load_profiles_from_file();
+    load_history();
+
+    sync_interpreters();
+    
+    // Validate and cleanup
+    validate_and_cleanup_config();
+
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        "Loading Assisted Deployment and Departure...",
+    );
+
+    // Clear any existing quick access items first
+    let config = CONFIG.lock().unwrap().clone();
+    for program in &config.programs_to_launch {
+        remove_quick_access(&format!("QA_ITEM_{}", program.name));
+    }
+    
+    // Setup programs
+    for program in &config.programs_to_launch {
+        setup_program(program);
+    }
+    register_keybind_with_string(LAUNCH_ALL_KEYBIND, keybind_callback, "").revert_on_unload();
+    setup_global_quick_access(LAUNCH_ALL_KEYBIND, "Launch all programs", [80, 200, 90, 255], launch_all_glyph);
+    register_keybind_with_string(KILL_ALL_KEYBIND, keybind_callback, "").revert_on_unload();
+    setup_global_quick_access(KILL_ALL_KEYBIND, "Close all kill-list programs", [220, 70, 60, 255], kill_all_glyph);
+    let session = load_session_marker();
+    launch_in_order(
+        config
+            .programs_to_launch
+            .iter()
+            .filter(|p| p.enabled && p.triggers.contains(&LaunchTrigger::OnAddonLoad))
+            .filter(|p| {
+                let skip = p.launch_once_per_session && session.launched.contains(&p.name);
+                if skip {
+                    log::log(
+                        LogLevel::Info,
+                        "SYSTEM",
+                        &format!("Skipping {}, already launched this game session", p.display_name),
+                    );
+                }
+                !skip
+            })
+            .cloned()
+            .collect(),
+    );
+    
+    spawn_keep_alive_watcher();
+    spawn_memory_watcher();
+    spawn_map_watcher();
+    spawn_config_watcher();
+    if first_run {
+        start_onboarding();
+    }
+
+    register_render(RenderType::OptionsRender, render!(render_options)).revert_on_unload();
+    register_render(RenderType::Render, render!(render_popup)).revert_on_unload();
+    register_render(RenderType::Render, render!(render_process_picker)).revert_on_unload();
+    register_render(RenderType::Render, render!(render_destructive_confirmation)).revert_on_unload();
+    register_render(RenderType::Render, render!(apply_pending_config_reload)).revert_on_unload();
+    // nexus takes the `windows` crate's HWND/WPARAM/LPARAM, transparent wrappers around the
+    // same raw values, so the windows-sys signature is the same function pointer ABI
+    let drop_wnd_proc = unsafe {
+        std::mem::transmute::<extern "C-unwind" fn(HWND, u32, WPARAM, LPARAM) -> u32, RawWndProc>(drop_wnd_proc)
+    };
+    register_wnd_proc(drop_wnd_proc).revert_on_unload();
+}
+
+fn unload() {
+    KEEP_ALIVE_ACTIVE.store(false, Ordering::SeqCst);
+    disable_file_drops();
+
+    // Stops and joins every background task; nothing new is spawned after this, so the
+    // launches and kills below run their hooks right here, with UNLOAD_HOOK_TIMEOUT
+    join_background_tasks();
+
+    // The game is shutting down, so these are fire-and-forget: we can't wait for
+    // them to start, and Quick Access only shows them if show_in_quick_access is set
+    let unload_launches: Vec<ProgramToLaunch> = CONFIG
+        .lock()
+        .unwrap()
+        .programs_to_launch
+        .iter()
+        .filter(|p| p.enabled && p.triggers.contains(&LaunchTrigger::OnUnload))
+        .cloned()
+        .collect();
+    for program in &unload_launches {
+        if launch_allowed(program) && run_pre_launch_hook(program) {
+            start_program(program, true);
+        }
+    }
+
+    let kill_list = unload_kill_targets();
+    if !kill_list.is_empty() {
+        cleanup_processes(&kill_list);
+    }
+
+    // After the launches above, so their counts are kept
+    save_config_to_file();
+    // Events recorded while the tasks above were finishing
+    flush_history();
+    close_mumble_link();
+    log::log(LogLevel::Info, "SYSTEM", "Unloaded.");
+}
+// Everything unload closes: the kill list plus programs set to close on unload
+fn unload_kill_targets() -> Vec<KillTarget> {
+    let config = CONFIG.lock().unwrap();
+    let mut list: Vec<KillTarget> = config
+        .programs_to_kill
+        .iter()
+        .map(|entry| KillTarget::from_kill_entry(entry, &config))
+        .collect();
+    for program in &config.programs_to_launch {
+        // Documents are opened by another app, there is no process of ours to close,
+        // and programs we just launched for unload shouldn't be killed right away
+        if program.close_on_unload
+            && get_document_path(&program.path).is_none()
+            && !program.triggers.contains(&LaunchTrigger::OnUnload)
+        {
+            if let Some(target) = KillTarget::for_program(program) {
+                if !list.iter().any(|t| t.name == target.name && t.exe_path == target.exe_path) {
+                    list.push(target);
+                }
+            }
+        }
+    }
+    list
+}
+
+// Lists the running processes unload would close, without touching them
+fn preview_kill_list() -> Vec<String> {
+    let targets = unload_kill_targets();
+    let protected = CONFIG.lock().unwrap().protected_processes.clone();
+    let targets: Vec<&KillTarget> = targets
+        .iter()
+        .filter(|t| !is_protected_process(&t.name, &protected))
+        .collect();
+    with_processes(Duration::ZERO, |sys| {
+        collect_kill_pids(sys, &targets, &protected, false)
+            .into_iter()
+            .filter_map(|(pid, _)| sys.process(pid).map(|p| format!("{} (PID: {})", p.name(), pid)))
+            .collect()
+    })
+}
+
+// Handler of the "kill all" keybind and tile: closes what unload would, right away.
+// cleanup_processes still applies the game and protected-process filters.
+fn kill_all_processes() {
+    let targets = unload_kill_targets();
+    let closing: Vec<String> = CONFIG
+        .lock()
+        .unwrap()
+        .programs_to_launch
+        .iter()
+        .filter(|p| p.close_on_unload)
+        .map(|p| p.name.clone())
+        .collect();
+    // Keep alive would start them right back up
+    for name in &closing {
+        set_keep_alive_paused(name, true);
+    }
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Closing all kill-list processes ({} targets)", targets.len()),
+    );
+    cleanup_processes_in_background(targets);
+}
+
+// Keeps the UI and keybind handlers responsive while waiting on a graceful close
+fn cleanup_processes_in_background(targets: Vec<KillTarget>) {
+    spawn_background_task(move || {
+        cleanup_processes(&targets);
+    });
+}
+
+// The game itself is always protected, even if removed from the list
+fn is_protected_process(name: &str, protected: &[String]) -> bool {
+    name.eq_ignore_ascii_case(GAME_PROCESS_NAME) || protected.iter().any(|p| p.eq_ignore_ascii_case(name))
+}
+
+// Walks the parent PID relationships to find every transitive child of `root`
+fn collect_descendants(sys: &System, root: Pid) -> Vec<Pid> {
+    let mut children_of: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for p in sys.processes().values() {
+        if let Some(parent) = p.parent() {
+            children_of.entry(parent).or_default().push(p.pid());
+        }
+    }
+
+    let mut descendants = Vec::new();
+    let mut stack = vec![root];
+    while let Some(pid) = stack.pop() {
+        if let Some(children) = children_of.get(&pid) {
+            for &child in children {
+                // Guard against PID reuse creating a cycle
+                if child != root && !descendants.contains(&child) {
+                    descendants.push(child);
+                    stack.push(child);
+                }
+            }
+        }
+    }
+    descendants
+}
+
+// (pid, graceful) for every process matching a target and, if requested, its process tree.
+// Protected processes found in a tree are left out.
+fn collect_kill_pids(
+    sys: &System,
+    targets: &[&KillTarget],
+    protected: &[String],
+    log_skipped: bool,
+) -> Vec<(Pid, bool)> {
+    let mut pids: Vec<(Pid, bool)> = Vec::new();
+    for target in targets {
+        let matched: Vec<Pid> = sys
+            .processes()
+            .values()
+            .filter(|p| target.matches(p))
+            .map(|p| p.pid())
+            .collect();
+        for pid in matched {
+            let mut tree = vec![pid];
+            if target.kill_children {
+                tree.extend(collect_descendants(sys, pid));
+            }
+            for member in tree {
+                let Some(process) = sys.process(member) else {
+                    continue;
+                };
+                if is_protected_process(process.name(), protected) {
+                    if log_skipped {
+                        log::log(
+                            LogLevel::Warning,
+                            "SYSTEM",
+                            &format!(
+                                "Skipping {} (PID: {}) in the process tree of {}: it is protected",
+                                process.name(),
+                                member,
+                                target.name
+                            ),
+                        );
+                    }
+                    continue;
+                }
+                if !pids.iter().any(|(p, _)| *p == member) {
+                    pids.push((member, target.graceful));
+                }
+            }
+        }
+    }
+    pids
+}
+
+// Returns the PIDs it went after, so a caller can wait for exactly those to exit
+fn cleanup_processes(targets: &[KillTarget]) -> Vec<Pid> {
+    // Unload closes its kill list itself after the background tasks are gone, so it waits
+    // and runs the hooks on this thread
+    let on_unload = UNLOADING.load(Ordering::SeqCst);
+    let (timeout, protected, dry_run) = {
+        let config = CONFIG.lock().unwrap();
+        (
+            Duration::from_millis(config.graceful_shutdown_timeout_ms),
+            config.protected_processes.clone(),
+            config.dry_run,
+        )
+    };
+
+    let safe_targets: Vec<_> = targets
+        .iter()
+        .filter(|t| {
+            let is_protected = is_protected_process(&t.name, &protected);
+            if is_protected {
+                log::log(
+                    LogLevel::Warning,
+                    "SYSTEM",
+                    &format!("Skipping {}: it is on the protected process list", t.name),
+                );
+            }
+            !is_protected
+        })
+        .collect();
+    if safe_targets.is_empty() {
+        return Vec::new();
+    }
+
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!(
+            "Closing processes: {:?}",
+            safe_targets.iter().map(|t| t.name.as_str()).collect::<Vec<_>>()
+        ),
+    );
+    // Kills always work on a fresh scan so a just-started process isn't missed
+    let mut pids = with_processes(Duration::ZERO, |sys| collect_kill_pids(sys, &safe_targets, &protected, true));
+    let targeted: Vec<Pid> = pids.iter().map(|(pid, _)| *pid).collect();
+    // Post-kill commands only run for targets that actually had something to close
+    let hooks: Vec<(String, String)> = with_processes(Duration::ZERO, |sys| {
+        safe_targets
+            .iter()
+            .filter(|t| sys.processes().values().any(|p| t.matches(p)))
+            .filter_map(|t| t.post_kill.clone().map(|hook| (t.name.clone(), hook)))
+            .collect()
+    });
+
+    if dry_run {
+        with_processes(Duration::ZERO, |sys| {
+            for (pid, graceful) in &pids {
+                let name = sys.process(*pid).map_or("(exited)", |p| p.name());
+                log::log(
+                    LogLevel::Info,
+                    "SYSTEM",
+                    &format!(
+                        "[Dry run] Would {} {} (PID: {})",
+                        if *graceful { "close" } else { "kill" },
+                        name,
+                        pid
+                    ),
+                );
+            }
+        });
+        for (name, hook) in &hooks {
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!("[Dry run] Would run post-kill command for {}: {}", name, hook),
+            );
+        }
+        return Vec::new();
+    }
+
+    // Names for the history, a gracefully closed process can't be looked up anymore
+    let names: HashMap<Pid, String> = with_processes(Duration::ZERO, |sys| {
+        pids.iter()
+            .filter_map(|(pid, _)| sys.process(*pid).map(|p| (*pid, p.name().to_string())))
+            .collect()
+    });
+
+    // Recorded once the process list is released, writing history must not block other scans
+    let mut events: Vec<(String, String)> = Vec::new();
+    if !timeout.is_zero() && pids.iter().any(|(_, graceful)| *graceful) {
+        // Processes without windows can't be asked to close, they go straight to kill
+        let mut waiting: Vec<Pid> = pids
+            .iter()
+            .filter(|(pid, graceful)| *graceful && request_process_close(pid.as_u32()))
+            .map(|(pid, _)| *pid)
+            .collect();
+        let deadline = Instant::now() + timeout;
+        while !waiting.is_empty() && Instant::now() < deadline {
+            if on_unload {
+                thread::sleep(Duration::from_millis(100));
+            } else if !sleep_unless_unloading(Duration::from_millis(100)) {
+                break;
+            }
+            // Only hold the process list briefly so the UI isn't blocked while we wait
+            with_processes(Duration::ZERO, |sys| waiting.retain(|pid| sys.process(*pid).is_some()));
+        }
+        with_processes(Duration::ZERO, |sys| {
+            pids.retain(|(pid, _)| {
+                let alive = sys.process(*pid).is_some();
+                if !alive {
+                    log::log(
+                        LogLevel::Info,
+                        "SYSTEM",
+                        &format!("Closed gracefully (PID: {})", pid),
+                    );
+                    events.push((
+                        names.get(pid).cloned().unwrap_or_else(|| "(unknown)".to_string()),
+                        format!("Closed gracefully (PID: {})", pid),
+                    ));
+                }
+                alive
+            })
+        });
+    }
+
+    with_processes(Duration::ZERO, |sys| {
+        for (pid, _) in pids {
+            if let Some(p) = sys.process(pid) {
+                log::log(
+                    LogLevel::Info,
+                    "SYSTEM",
+                    &format!("Killing: {} (PID: {})", p.name(), p.pid()),
+                );
+                let outcome = if p.kill() { "Killed" } else { "Failed to kill" };
+                events.push((p.name().to_string(), format!("{} (PID: {})", outcome, p.pid())));
+            }
+        }
+    });
+    for (program, outcome) in events {
+        record_event(&program, outcome);
+    }
+
+    // Kills also run from the render thread and keybinds; the hooks must not hold those up
+    if on_unload {
+        for (name, hook) in &hooks {
+            run_hook("Post-kill", name, hook, &HashMap::new());
+        }
+    } else if !hooks.is_empty() {
+        spawn_background_task(move || {
+            for (name, hook) in &hooks {
+                run_hook("Post-kill", name, hook, &HashMap::new());
+            }
+        });
+    }
+    targeted
+}
+
+// --- Onboarding ---
+// Companion apps offered on the first run, with the places they are usually installed to
+struct KnownTool {
+    display_name: &'static str,
+    exe_name: &'static str,
+    candidates: &'static [&'static str],
+    args: &'static str,
+}
+
+const KNOWN_TOOLS: [KnownTool; 6] = [
+    KnownTool {
+        display_name: "Discord",
+        exe_name: "Update.exe",
+        candidates: &["%LOCALAPPDATA%\\Discord\\Update.exe"],
+        args: "--processStart Discord.exe",
+    },
+    KnownTool {
+        display_name: "OBS Studio",
+        exe_name: "obs64.exe",
+        candidates: &["%ProgramFiles%\\obs-studio\\bin\\64bit\\obs64.exe"],
+        args: "",
+    },
+    KnownTool {
+        display_name: "Blish HUD",
+        exe_name: "Blish HUD.exe",
+        candidates: &[
+            "{GAME_DIR}\\Blish HUD\\Blish HUD.exe",
+            "%USERPROFILE%\\Documents\\Blish HUD\\Blish HUD.exe",
+            "%LOCALAPPDATA%\\Programs\\Blish HUD\\Blish HUD.exe",
+            "%ProgramFiles%\\Blish HUD\\Blish HUD.exe",
+        ],
+        args: "",
+    },
+    KnownTool {
+        display_name: "GW2TacO",
+        exe_name: "GW2TacO.exe",
+        candidates: &[
+            "{GAME_DIR}\\GW2TacO\\GW2TacO.exe",
+            "{GAME_DIR}\\TacO\\GW2TacO.exe",
+            "%USERPROFILE%\\Documents\\GW2TacO\\GW2TacO.exe",
+            "%ProgramFiles%\\GW2TacO\\GW2TacO.exe",
+        ],
+        args: "",
+    },
+    KnownTool {
+        display_name: "Elite Insights",
+        exe_name: "GuildWars2EliteInsights.exe",
+        candidates: &[
+            "%USERPROFILE%\\Documents\\GW2EI\\GuildWars2EliteInsights.exe",
+            "%LOCALAPPDATA%\\Programs\\GW2 Elite Insights\\GuildWars2EliteInsights.exe",
+        ],
+        args: "",
+    },
+    KnownTool {
+        display_name: "arcdps Log Manager",
+        exe_name: "GW2 Log Manager.exe",
+        candidates: &[
+            "%LOCALAPPDATA%\\Programs\\ArcdpsLogManager\\GW2 Log Manager.exe",
+            "%USERPROFILE%\\Documents\\ArcdpsLogManager\\GW2 Log Manager.exe",
+        ],
+        args: "",
+    },
+];
+
+// Installers register their executable under "App Paths", per machine or per user
+fn app_path_from_registry(exe_name: &str) -> Option<String> {
+    let subkey = to_wide(&format!(
+        "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\App Paths\\{}",
+        exe_name
+    ));
+    [HKEY_LOCAL_MACHINE, HKEY_CURRENT_USER].into_iter().find_map(|root: HKEY| {
+        let mut buffer = vec![0u16; 1024];
+        let mut size = (buffer.len() * 2) as u32;
+        let status = unsafe {
+            RegGetValueW(
+                root,
+                subkey.as_ptr(),
+                std::ptr::null(),
+                RRF_RT_REG_SZ,
+                std::ptr::null_mut(),
+                buffer.as_mut_ptr() as *mut c_void,
+                &mut size,
+            )
+        };
+        (status == 0).then(|| from_wide(&buffer)).map(|path| path.trim_matches('"').to_string())
+    })
+}
+
+// Known tools installed on this machine, as ready-to-add programs
+fn detect_known_tools() -> Vec<ProgramToLaunch> {
+    KNOWN_TOOLS
+        .iter()
+        .filter_map(|tool| {
+            let exe = tool
+                .candidates
+                .iter()
+                .map(|candidate| expand_path_variables(candidate))
+                .chain(app_path_from_registry(tool.exe_name))
+                .find(|path| Path::new(path).is_file())?;
+            let path = match tool.args {
+                "" => exe,
+                args => format!("\"{}\" {}", exe, args),
+            };
+            let mut program = ProgramToLaunch {
+                display_name: tool.display_name.to_string(),
+                path,
+                ..Default::default()
+            };
+            split_program_command(&mut program);
+            Some(program)
+        })
+        .collect()
+}
+
+// Looks for known tools in the background and offers them in the options if any turn up
+fn start_onboarding() {
+    spawn_background_task(|| {
+        let found = detect_known_tools();
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("First run, found {} known tool(s) to offer", found.len()),
+        );
+        if !found.is_empty() {
+            *ONBOARDING.lock().unwrap() = Some(found.into_iter().map(|program| (program, true)).collect());
+        }
+    });
+}
+
+// Gives each program a unique internal name and sets up its keybind and Quick Access tile
+fn add_programs(programs: Vec<ProgramToLaunch>) {
+    let added: Vec<ProgramToLaunch> = {
+        let mut config = CONFIG.lock().unwrap();
+        programs
+            .into_iter()
+            .map(|mut program| {
+                program.name = unique_program_name(&sanitize_identifier(&program.display_name), &config.programs_to_launch);
+                config.programs_to_launch.push(program.clone());
+                program
+            })
+            .collect()
+    };
+    for program in &added {
+        setup_program(program);
+        log::log(
+            LogLevel::Info,
+            "SYSTEM",
+            &format!("Added {}: {}", program.display_name, program.path),
+        );
+    }
+    refresh_duplicate_paths(&CONFIG.lock().unwrap().programs_to_launch);
+    save_config_to_file();
+}
+
+fn render_onboarding(ui: &Ui) {
+    let mut onboarding = ONBOARDING.lock().unwrap();
+    let Some(found) = onboarding.as_mut() else {
+        return;
+    };
+
+    let mut close = false;
+    ui.text_colored([0.4, 0.8, 1.0, 1.0], "Add detected tools");
+    ui.text("These companion apps were found on this computer. Tick the ones to launch with the game.");
+    for (program, selected) in found.iter_mut() {
+        ui.checkbox(&format!("{}##onboarding_{}", program.display_name, program.path), selected);
+        ui.same_line();
+        ui.text_colored([0.6, 0.6, 0.6, 1.0], &program.path);
+    }
+    if ui.button("Add selected##onboarding") {
+        let selected: Vec<ProgramToLaunch> = found
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(program, _)| program.clone())
+            .collect();
+        add_programs(selected);
+        close = true;
+    }
+    ui.same_line();
+    if ui.button("Dismiss##onboarding") {
+        close = true;
+    }
+    ui.separator();
+    if close {
+        *onboarding = None;
+    }
+}
+
+// Shareable code for one program: its RON, base64-encoded. Launch stats stay local.
+fn encode_program_code(program: &ProgramToLaunch) -> Result<String, String> {
+    let mut shared = ProgramToLaunch {
+        launch_count: 0,
+        ..program.clone()
+    };
+    // Tokens and passwords are left for the recipient to fill in
+    for &index in &program.sensitive_args {
+        if let Some(arg) = shared.args.get_mut(index) {
+            arg.clear();
+        }
+    }
+    let ron = ron::to_string(&shared).map_err(|e| e.to_string())?;
+    Ok(BASE64.encode(ron))
+}
+
+// Reverse of encode_program_code. The path has to exist here too, like a path typed in.
+fn decode_program_code(code: &str) -> Result<ProgramToLaunch, String> {
+    let bytes = BASE64
+        .decode(code.trim())
+        .map_err(|_| "the code is not a valid program code".to_string())?;
+    let ron = String::from_utf8(bytes).map_err(|_| "the code is not a valid program code".to_string())?;
+    let program: ProgramToLaunch =
+        ron::from_str(&ron).map_err(|e| format!("the code doesn't describe a program ({})", e))?;
+    let exists = is_url(&program.path)
+        || get_program_command(&program).is_some_and(|(exe_path, _)| Path::new(&exe_path).exists());
+    if !exists {
+        return Err(format!("{} does not exist on this computer", program.path));
+    }
+    Ok(program)
+}
+
+// Position of a trigger when sorting by trigger: automatic ones first, then manual ones
+fn trigger_rank(trigger: &LaunchTrigger) -> u8 {
+    match trigger {
+        LaunchTrigger::OnAddonLoad => 0,
+        LaunchTrigger::OnMapEnter(_) => 1,
+        LaunchTrigger::OnKeybind => 2,
+        LaunchTrigger::Toggle => 3,
+        LaunchTrigger::OnMapLeave(_) => 4,
+        LaunchTrigger::OnUnload => 5,
+    }
+}
+
+// Stable, so favorites and the rest each keep their relative order
+fn favorites_first(programs: &mut [ProgramToLaunch]) {
+    programs.sort_by_key(|p| !p.is_favorite);
+}
+
+// Reorders the launch list by display name, or by trigger and then name, and re-adds
+// the Quick Access tiles since their order follows the list
+fn sort_programs(by_trigger: bool) {
+    reorder_programs(|programs| {
+        programs.sort_by_cached_key(|p| {
+            let rank = if by_trigger {
+                p.triggers.iter().map(trigger_rank).min().unwrap_or(u8::MAX)
+            } else {
+                0
+            };
+            (rank, p.display_name.to_lowercase())
+        })
+    });
+}
+
+// Quick Access shows tiles in registration order, so they're re-added after the list moves
+fn reorder_programs(reorder: impl FnOnce(&mut Vec<ProgramToLaunch>)) {
+    let (old_programs, new_programs) = {
+        let mut config = CONFIG.lock().unwrap();
+        let old_programs = config.programs_to_launch.clone();
+        reorder(&mut config.programs_to_launch);
+        favorites_first(&mut config.programs_to_launch);
+        (old_programs, config.programs_to_launch.clone())
+    };
+    for program in &old_programs {
+        remove_quick_access(&format!("QA_ITEM_{}", program.name));
+    }
+    for program in new_programs.iter().filter(|p| p.enabled) {
+        setup_quick_access_for_program(program);
+    }
+    save_config_to_file();
+}
+
+const UNDO_REMOVE_WINDOW: Duration = Duration::from_secs(10);
+
+// Puts the last removed program back where it was, unless the undo window has passed
+fn undo_program_removal() {
+    let Some((program, index, removed_at)) = REMOVED_PROGRAM.lock().unwrap().take() else {
+        return;
+    };
+    if removed_at.elapsed() >= UNDO_REMOVE_WINDOW {
+        return;
+    }
+    reorder_programs(|programs| programs.insert(index.min(programs.len()), program.clone()));
+    if uses_keybind(&program) || program.show_in_quick_access {
+        register_program_keybind(&program);
+    }
+    refresh_duplicate_paths(&CONFIG.lock().unwrap().programs_to_launch);
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Restored removed program: {}", program.display_name),
+    );
+}
+
+fn find_executables(dir: &Path, recursive: bool, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|e| e.path())).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            if recursive {
+                find_executables(&path, recursive, found);
+            }
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("exe")) {
+            found.push(path);
+        }
+    }
+}
+
+// Adds every executable in `folder` that isn't configured yet. They start as keybind-only
+// and off Quick Access, so a folder full of tools doesn't clutter anything until enabled.
+fn add_programs_from_folder(folder: &Path, recursive: bool) {
+    let mut found = Vec::new();
+    find_executables(folder, recursive, &mut found);
+    let mut known: HashSet<String> = CONFIG
+        .lock()
+        .unwrap()
+        .programs_to_launch
+        .iter()
+        .filter_map(normalized_exe_path)
+        .collect();
+
+    let programs: Vec<ProgramToLaunch> = found
+        .into_iter()
+        .filter_map(|exe| {
+            let mut program = ProgramToLaunch {
+                display_name: exe.file_name()?.to_string_lossy().to_string(),
+                path: exe.to_string_lossy().to_string(),
+                triggers: BTreeSet::from([LaunchTrigger::OnKeybind]),
+                show_in_quick_access: false,
+                ..Default::default()
+            };
+            split_program_command(&mut program);
+            // Also skips duplicates within the folder, e.g. through a junction
+            known.insert(normalized_exe_path(&program)?).then_some(program)
+        })
+        .collect();
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Adding {} new program(s) from {}", programs.len(), folder.display()),
+    );
+    add_programs(programs);
+}
+
+// --- Drag and Drop ---
+// Lets Explorer drop files onto the game window, done once the options are first shown
+fn enable_file_drops() {
+    if DROP_TARGET.load(Ordering::SeqCst) != 0 {
+        return;
+    }
+    if let Some(hwnd) = find_main_window(std::process::id()) {
+        unsafe { DragAcceptFiles(hwnd, TRUE) };
+        DROP_TARGET.store(hwnd as usize, Ordering::SeqCst);
+    }
+}
+
+fn disable_file_drops() {
+    let hwnd = DROP_TARGET.swap(0, Ordering::SeqCst);
+    if hwnd != 0 {
+        unsafe { DragAcceptFiles(hwnd as HWND, 0) };
+    }
+}
+
+fn options_open() -> bool {
+    OPTIONS_LAST_RENDERED
+        .lock()
+        .unwrap()
+        .is_some_and(|at| at.elapsed() < Duration::from_millis(500))
+}
+
+// Collects dropped paths for render_options; config changes stay on the render thread
+extern "C-unwind" fn drop_wnd_proc(_window: HWND, msg: u32, wparam: WPARAM, _lparam: LPARAM) -> u32 {
+    if msg != WM_DROPFILES {
+        return msg;
+    }
+    let hdrop = wparam as HDROP;
+    let mut paths = Vec::new();
+    unsafe {
+        let count = DragQueryFileW(hdrop, u32::MAX, std::ptr::null_mut(), 0);
+        for index in 0..count {
+            let len = DragQueryFileW(hdrop, index, std::ptr::null_mut(), 0);
+            let mut buffer = vec![0u16; len as usize + 1];
+            DragQueryFileW(hdrop, index, buffer.as_mut_ptr(), buffer.len() as u32);
+            paths.push(from_wide(&buffer));
+        }
+        DragFinish(hdrop);
+    }
+    if options_open() {
+        DROPPED_FILES.lock().unwrap().extend(paths);
+    } else {
+        log::log(
+            LogLevel::Debug,
+            "SYSTEM",
+            "Ignoring files dropped while the options are closed",
+        );
+    }
+    0
+}
+
+// Adds dropped executables and shortcuts as new programs
+fn add_dropped_files() {
+    let dropped = std::mem::take(&mut *DROPPED_FILES.lock().unwrap());
+    if dropped.is_empty() {
+        return;
+    }
+    let programs: Vec<ProgramToLaunch> = dropped
+        .into_iter()
+        .filter_map(|path| {
+            let droppable = Path::new(&path)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("exe") || ext.eq_ignore_ascii_case("lnk"));
+            if !droppable {
+                log::log(
+                    LogLevel::Warning,
+                    "SYSTEM",
+                    &format!("Only .exe and .lnk files can be dropped, ignoring {}", path),
+                );
+                return None;
+            }
+            let mut program = ProgramToLaunch {
+                display_name: get_program_name_from_command(&path)?,
+                path,
+                ..Default::default()
+            };
+            split_program_command(&mut program);
+            Some(program)
+        })
+        .collect();
+    add_programs(programs);
+}
+
+// --- UI Rendering ---
+fn render_popup(ui: &Ui) {
+    let auto_dismiss_secs = CONFIG.lock().unwrap().popup_auto_dismiss_secs;
+    let mut pending_launch = PENDING_LAUNCH_CONFIRMATION.lock().unwrap();
+    let mut dont_ask_again = DONT_ASK_AGAIN.lock().unwrap();
+    let mut close_popup = false;
+    let mut answer: Option<AlreadyRunningAction> = None;
+    let mut remember: Option<(String, AlreadyRunningAction)> = None;
+    let program_to_launch = pending_launch.clone();
+    if let Some((program, asked_at)) = program_to_launch {
+        // Counts as "No" once the time is up
+        let remaining_secs = (auto_dismiss_secs > 0)
+            .then(|| auto_dismiss_secs.saturating_sub(asked_at.elapsed().as_secs()));
+        if remaining_secs == Some(0) {
+            log::log(
+                LogLevel::Info,
+                "SYSTEM",
+                &format!("No answer for {}, not launching another instance", program.display_name),
+            );
+            *pending_launch = None;
+            *dont_ask_again = false;
+            return;
+        }
+        let filename = get_program_process_name(&program).unwrap_or_else(|| "program".to_string());
+        let mut open = true;
+        Window::new(&format!("'{}' Already Running", filename))
+            .opened(&mut open)
+            .always_auto_resize(true)
+            .collapsible(false)
+            .focus_on_appearing(true)
+            .build(ui, || {
+                ui.text("This program is already running.");
+                ui.text("Do you want to open another instance?");
+                if let Some(remaining_secs) = remaining_secs {
+                    ui.text_colored(
+                        [0.6, 0.6, 0.6, 1.0],
+                        format!("Closing in {}s (No)", remaining_secs),
+                    );
+                }
+                ui.separator();
+                ui.checkbox("Don't ask again for this program", &mut *dont_ask_again);
+                if ui.button("Yes") {
+                    force_launch_process(&program);
+                    close_popup = true;
+                    answer = Some(AlreadyRunningAction::LaunchAnyway);
+                }
+                ui.same_line();
+                if ui.button("No") {
+                    close_popup = true;
+                    answer = Some(AlreadyRunningAction::DoNothing);
+                }
+            });
+        if !open {
+            close_popup = true;
+        }
+        remember = answer.filter(|_| *dont_ask_again).map(|action| (program.name.clone(), action));
+    }
+    if close_popup {
+        *pending_launch = None;
+        *dont_ask_again = false;
+    }
+    drop(pending_launch);
+    drop(dont_ask_again);
+
+    // Stored as the program's "If already running" choice, so the popup is skipped next time
+    if let Some((name, action)) = remember {
+        let updated = {
+            let mut config = CONFIG.lock().unwrap();
+            match config.programs_to_launch.iter_mut().find(|p| p.name == name) {
+                Some(prog) => {
+                    prog.on_already_running = action;
+                    true
+                }
+                None => false,
+            }
+        };
+        if updated {
+            save_config_to_file();
+        }
+    }
+}
+
+fn running_processes() -> Vec<(String, u32)> {
+    let mut processes: Vec<(String, u32)> = with_processes(Duration::ZERO, |sys| {
+        sys.processes()
+            .values()
+            .map(|p| (p.name().to_string(), p.pid().as_u32()))
+            .collect()
+    });
+    processes.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()).then(a.1.cmp(&b.1)));
+    processes
+}
+
+fn render_process_picker(ui: &Ui) {
+    let mut picker_state = PROCESS_PICKER.lock().unwrap();
+    let Some(picker) = picker_state.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut picked: Option<String> = None;
+    Window::new("Pick running process")
+        .opened(&mut open)
+        .size([350.0, 400.0], Condition::FirstUseEver)
+        .collapsible(false)
+        .build(ui, || {
+            ui.set_next_item_width(250.0);
+            InputText::new(ui, "##picker_filter", &mut picker.filter)
+                .hint("Search...")
+                .build();
+            ui.same_line();
+            if ui.button("Refresh##picker") {
+                picker.processes = running_processes();
+            }
+            ui.separator();
+
+            let filter = picker.filter.trim().to_lowercase();
+            for (name, pid) in &picker.processes {
+                if !filter.is_empty() && !name.to_lowercase().contains(&filter) {
+                    continue;
+                }
+                if Selectable::new(&format!("{} (PID: {})", name, pid)).build(ui) {
+                    picked = Some(name.clone());
+                }
+            }
+        });
+
+    if let Some(name) = picked {
+        let added = {
+            let mut config = CONFIG.lock().unwrap();
+            let added = !config.programs_to_kill.contains(&name);
+            if added {
+                config.programs_to_kill.push(name.clone());
+            }
+            added
+        };
+        if added {
+            save_config_to_file();
+            log::log(LogLevel::Info, "SYSTEM", &format!("Added {} to the kill list", name));
+        }
+        open = false;
+    }
+    if !open {
+        *picker_state = None;
+    }
+}
+
+fn render_options(ui: &Ui) {
+    *OPTIONS_LAST_RENDERED.lock().unwrap() = Some(Instant::now());
+    enable_file_drops();
+    add_dropped_files();
+
+    ui.text("Manage external programs to launch/kill.");
+    ui.text_colored([0.6, 0.6, 0.6, 1.0], "Drop an .exe or shortcut onto the game window to add it.");
+    render_profile_selector(ui);
+    render_onboarding(ui);
+
+    let mut launches_enabled = CONFIG.lock().unwrap().launches_enabled;
+    if ui.checkbox("Enable launches", &mut launches_enabled) {
+        CONFIG.lock().unwrap().launches_enabled = launches_enabled;
+        save_config_to_file();
+    }
+    ui.same_line();
+    let mut dry_run = CONFIG.lock().unwrap().dry_run;
+    if ui.checkbox("Dry run (only log launches and kills)", &mut dry_run) {
+        CONFIG.lock().unwrap().dry_run = dry_run;
+        save_config_to_file();
+    }
+    if !launches_enabled {
+        ui.text_colored([0.9, 0.6, 0.2, 1.0], "Nothing will be launched, kills still apply.");
+    }
+    ui.separator();
+    
+    // Handle Programs to Launch section
+    render_programs_to_launch_section(ui);
+    
+    // Handle Programs to Kill section
+    render_programs_to_kill_section(ui);
+
+    // Handle Protected Processes section
+    render_protected_processes_section(ui);
+
+    render_general_settings_section(ui);
+
+    render_history_section(ui);
+
+    ui.separator();
+    if ui.button("Validate & Clean") {
+        let summary = revalidate_config();
+        *VALIDATION_RESULT.lock().unwrap() = Some(format!(
+            "Removed {} program{} with a missing path, fixed {}",
+            summary.removed,
+            if summary.removed == 1 { "" } else { "s" },
+            summary.fixed
+        ));
+    }
+    if let Some(result) = &*VALIDATION_RESULT.lock().unwrap() {
+        ui.same_line();
+        ui.text(result);
+    }
+    if ui.button("Reset to Defaults") {
+        *PENDING_DESTRUCTIVE_ACTION.lock().unwrap() = Some(DestructiveAction::ResetToDefaults);
+    }
+}
+
+fn render_destructive_confirmation(ui: &Ui) {
+    let Some(action) = *PENDING_DESTRUCTIVE_ACTION.lock().unwrap() else {
+        return;
+    };
+    let (title, message) = match action {
+        DestructiveAction::ResetToDefaults => (
+            "Reset to Defaults",
+            "This removes every program, kill-list entry and setting of the current profile.",
+        ),
+        DestructiveAction::RemoveAllPrograms => (
+            "Remove All Programs",
+            "This removes every program from the launch list of the current profile.",
+        ),
+    };
+
+    let mut open = true;
+    let mut confirmed = false;
+    let mut cancelled = false;
+    Window::new(title)
+        .opened(&mut open)
+        .always_auto_resize(true)
+        .collapsible(false)
+        .focus_on_appearing(true)
+        .build(ui, || {
+            ui.text(message);
+            ui.text("It can't be undone.");
+            ui.separator();
+            if ui.button("Yes") {
+                confirmed = true;
+            }
+            ui.same_line();
+            if ui.button("No") {
+                cancelled = true;
+            }
+        });
+
+    if confirmed || cancelled || !open {
+        *PENDING_DESTRUCTIVE_ACTION.lock().unwrap() = None;
+    }
+    if confirmed {
+        match action {
+            DestructiveAction::ResetToDefaults => reset_to_defaults(),
+            DestructiveAction::RemoveAllPrograms => remove_all_programs(),
+        }
+    }
+}
+
+fn reset_to_defaults() {
+    *REMOVED_PROGRAM.lock().unwrap() = None;
+    let old_config = CONFIG.lock().unwrap().clone();
+    for program in &old_config.programs_to_launch {
+        teardown_program(program);
+    }
+    // The file format stays, or resetting would also move the settings file
+    *CONFIG.lock().unwrap() = Config {
+        config_format: old_config.config_format,
+        ..Default::default()
+    };
+    sync_interpreters();
+    validate_and_cleanup_config();
+    save_config_to_file();
+    log::log(LogLevel::Info, "SYSTEM", "Reset the current profile to defaults");
+}
+
+// Re-runs the load-time validation, e.g. after a drive with some of the programs came back
+// or went away. Keybinds and tiles are rebuilt since entries may be removed or renamed.
+fn revalidate_config() -> ValidationSummary {
+    *REMOVED_PROGRAM.lock().unwrap() = None;
+    let old_config = CONFIG.lock().unwrap().clone();
+    for program in &old_config.programs_to_launch {
+        teardown_program(program);
+    }
+    let summary = validate_and_cleanup_config();
+    let config = CONFIG.lock().unwrap().clone();
+    for program in &config.programs_to_launch {
+        setup_program(program);
+    }
+    if summary.removed > 0 {
+        save_config_to_file();
+    }
+    summary
+}
+
+fn remove_all_programs() {
+    *REMOVED_PROGRAM.lock().unwrap() = None;
+    let programs = std::mem::take(&mut CONFIG.lock().unwrap().programs_to_launch);
+    for program in &programs {
+        teardown_program(program);
+    }
+    refresh_duplicate_paths(&[]);
+    save_config_to_file();
+    log::log(
+        LogLevel::Info,
+        "SYSTEM",
+        &format!("Removed all {} programs from the launch list", programs.len()),
+    );
+}
+
+fn render_history_section(ui: &Ui) {
+    if !ui.collapsing_header("History", TreeNodeFlags::empty()) {
+        return;
+    }
+
+    if ui.button("Clear##history") {
+        HISTORY.lock().unwrap().clear();
+        schedule_history_save();
+    }
+    // A copy, so launches and kills can be recorded while the list is drawn
+    let history: Vec<HistoryEvent> = HISTORY.lock().unwrap().iter().cloned().collect();
+    if history.is_empty() {
+        ui.text_colored([0.6, 0.6, 0.6, 1.0], "No launches or kills recorded yet.");
+    }
+    for event in history.iter().rev() {
+        ui.text_colored([0.6, 0.6, 0.6, 1.0], &event.timestamp);
+        ui.same_line();
+        ui.text(format!("{}: {}", event.program, event.outcome));
+    }
+}
+
+fn render_general_settings_section(ui: &Ui) {
+    if !ui.collapsing_header("General Settings", TreeNodeFlags::empty()) {
+        return;
+    }
+
+    let mut changed = false;
+    let mut auto_dismiss_secs = CONFIG.lock().unwrap().popup_auto_dismiss_secs as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int("Auto-dismiss \"already running\" popup (s, 0 = off)", &mut auto_dismiss_secs)
+        .build()
+    {
+        CONFIG.lock().unwrap().popup_auto_dismiss_secs = auto_dismiss_secs.max(0) as u64;
+        changed = true;
+    }
+
+    let mut file_logging = CONFIG.lock().unwrap().file_logging;
+    if ui.checkbox("Write launches and kills to history.log", &mut file_logging) {
+        CONFIG.lock().unwrap().file_logging = file_logging;
+        FILE_LOGGING.store(file_logging, Ordering::Relaxed);
+        changed = true;
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text(get_history_log_path().display().to_string());
+    }
+
+    let mut defer_while_loading = CONFIG.lock().unwrap().defer_launches_while_loading;
+    if ui.checkbox("Hold back launches during loading screens", &mut defer_while_loading) {
+        CONFIG.lock().unwrap().defer_launches_while_loading = defer_while_loading;
+        changed = true;
+    }
+
+    let mut precheck_timeout_ms = CONFIG.lock().unwrap().precheck_timeout_ms as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int("Reachability check timeout (ms)", &mut precheck_timeout_ms)
+        .step(100)
+        .build()
+    {
+        CONFIG.lock().unwrap().precheck_timeout_ms = precheck_timeout_ms.max(100) as u64;
+        changed = true;
+    }
+
+    let mut test_launch_secs = CONFIG.lock().unwrap().test_launch_secs as i32;
+    ui.set_next_item_width(100.0);
+    if ui.input_int("Test button run time (s)", &mut test_launch_secs).build() {
+        CONFIG.lock().unwrap().test_launch_secs = test_launch_secs.max(1) as u64;
+        changed = true;
+    }
+
+    let mut max_concurrent = CONFIG.lock().unwrap().max_concurrent_launches as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int("Programs started at once on load and \"launch all\" (0 = no limit)", &mut max_concurrent)
+        .build()
+    {
+        CONFIG.lock().unwrap().max_concurrent_launches = max_concurrent.max(0) as usize;
+        changed = true;
+    }
+
+    let mut cooldown_ms = CONFIG.lock().unwrap().launch_cooldown_ms as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int("Launch cooldown for keybinds and Quick Access (ms)", &mut cooldown_ms)
+        .step(100)
+        .build()
+    {
+        CONFIG.lock().unwrap().launch_cooldown_ms = cooldown_ms.max(0) as u64;
+        changed = true;
+    }
+
+    let format_labels: Vec<&str> = CONFIG_FORMATS.iter().map(|(_, label)| *label).collect();
+    let mut format_idx = CONFIG_FORMATS
+        .iter()
+        .position(|(format, _)| *format == CONFIG.lock().unwrap().config_format)
+        .unwrap_or(0);
+    ui.set_next_item_width(200.0);
+    if ui.combo_simple_string("Settings file format", &mut format_idx, &format_labels) {
+        let format = CONFIG_FORMATS[format_idx].0;
+        CONFIG.lock().unwrap().config_format = format;
+        for config in PROFILES.lock().unwrap().profiles.values_mut() {
+            config.config_format = format;
+        }
+        changed = true;
+    }
+
+    let host_labels: Vec<&str> = POWERSHELL_HOSTS.iter().map(|(_, label)| *label).collect();
+    let mut host_idx = POWERSHELL_HOSTS
+        .iter()
+        .position(|(host, _)| *host == CONFIG.lock().unwrap().powershell_host)
+        .unwrap_or(0);
+    ui.set_next_item_width(250.0);
+    if ui.combo_simple_string("PowerShell for .ps1 scripts", &mut host_idx, &host_labels) {
+        CONFIG.lock().unwrap().powershell_host = POWERSHELL_HOSTS[host_idx].0;
+        changed = true;
+    }
+
+    let current_policy = CONFIG.lock().unwrap().powershell_execution_policy.clone();
+    let mut policy_idx = EXECUTION_POLICIES
+        .iter()
+        .position(|policy| policy.eq_ignore_ascii_case(&current_policy))
+        .unwrap_or(0);
+    ui.set_next_item_width(150.0);
+    if ui.combo_simple_string("Execution policy", &mut policy_idx, &EXECUTION_POLICIES) {
+        CONFIG.lock().unwrap().powershell_execution_policy = EXECUTION_POLICIES[policy_idx].to_string();
+        changed = true;
+    }
+
+    ui.separator();
+    ui.text("Script interpreters (extension -> command)");
+    ui.text_disabled(".ps1 uses the PowerShell settings above unless it has its own entry here");
+    let mut interpreters: Vec<(String, String)> = CONFIG.lock().unwrap().interpreters.clone().into_iter().collect();
+    interpreters.sort();
+    let mut remove_ext = None;
+    for (ext, command) in &interpreters {
+        if ui.small_button(format!("-##interpreter_{}", ext)) {
+            remove_ext = Some(ext.clone());
+        }
+        ui.same_line();
+        ui.text(format!(".{} -> {}", ext, command));
+    }
+    if let Some(ext) = remove_ext {
+        CONFIG.lock().unwrap().interpreters.remove(&ext);
+        changed = true;
+    }
+
+    let mut input = INTERPRETER_INPUT.lock().unwrap();
+    ui.set_next_item_width(60.0);
+    InputText::new(ui, "##interpreter_ext", &mut input.0).hint("ext").build();
+    ui.same_line();
+    ui.set_next_item_width(200.0);
+    InputText::new(ui, "##interpreter_command", &mut input.1)
+        .hint("e.g. powershell -File")
+        .build();
+    ui.same_line();
+    if ui.button("Add##interpreter") {
+        let ext = input.0.trim().trim_start_matches('.').to_ascii_lowercase();
+        let command = input.1.trim().to_string();
+        if !ext.is_empty() && !command.is_empty() {
+            CONFIG.lock().unwrap().interpreters.insert(ext, command);
+            input.0.clear();
+            input.1.clear();
+            changed = true;
+        }
+    }
+    drop(input);
+
+    if changed {
+        sync_interpreters();
+        save_config_to_file();
+    }
+}
+
+const VK_ESCAPE: usize = 0x1B;
+
+// Keybind string for the key held this frame, with modifiers. Only letters, digits and
+// function keys, whose names are the same in every keyboard layout.
+fn captured_key_combination(ui: &Ui) -> Option<String> {
+    let io = ui.io();
+    let key = io.keys_down.iter().enumerate().find_map(|(vk, down)| {
+        if !*down {
+            return None;
+        }
+        match vk {
+            0x30..=0x39 | 0x41..=0x5A => Some((vk as u8 as char).to_string()),
+            0x70..=0x87 => Some(format!("F{}", vk - 0x6F)),
+            _ => None,
+        }
+    })?;
+
+    let mut parts = Vec::new();
+    if io.key_alt {
+        parts.push("ALT".to_string());
+    }
+    if io.key_ctrl {
+        parts.push("CTRL".to_string());
+    }
+    if io.key_shift {
+        parts.push("SHIFT".to_string());
+    }
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+// Same combination regardless of modifier order, case and spacing ("shift + alt+1" == "ALT+SHIFT+1")
+fn normalize_keybind(keybind: &str) -> String {
+    let mut modifiers = Vec::new();
+    let mut keys = Vec::new();
+    for part in keybind.split('+').map(|p| p.trim().to_uppercase()).filter(|p| !p.is_empty()) {
+        match part.as_str() {
+            "ALT" | "CTRL" | "SHIFT" => modifiers.push(part),
+            _ => keys.push(part),
+        }
+    }
+    modifiers.sort();
+    modifiers.dedup();
+    modifiers.extend(keys);
+    modifiers.join("+")
+}
+
+// Default keys shared by more than one program, as (key, display names). Nexus doesn't
+// let addons read the actual bindings, so only the defaults set here can be compared.
+fn keybind_conflicts(programs: &[ProgramToLaunch]) -> Vec<(String, Vec<String>)> {
+    let mut by_key: HashMap<String, Vec<String>> = HashMap::new();
+    for prog in programs {
+        if !(uses_keybind(prog) || prog.show_in_quick_access) {
+            continue;
+        }
+        let key = normalize_keybind(&prog.keybind);
+        if !key.is_empty() {
+            by_key.entry(key).or_default().push(prog.display_name.clone());
+        }
+    }
+    let mut conflicts: Vec<_> = by_key.into_iter().filter(|(_, names)| names.len() > 1).collect();
+    conflicts.sort();
+    conflicts
+}
+
+const DEFAULT_GROUP: &str = "General";
+
+fn group_label(program: &ProgramToLaunch) -> &str {
+    program.group.as_deref().unwrap_or(DEFAULT_GROUP)
+}
+
+fn render_profile_selector(ui: &Ui) {
+    let (mut names, active) = {
+        let profiles = PROFILES.lock().unwrap();
+        (
+            profiles.profiles.keys().cloned().collect::<Vec<_>>(),
+            profiles.active_profile.clone(),
+        )
+    }; // Release lock before switching
+    names.sort();
+
+    let mut switch_to: Option<String> = None;
+    let mut active_idx = names.iter().position(|n| *n == active).unwrap_or(0);
+    ui.set_next_item_width(200.0);
+    if ui.combo_simple_string("Profile", &mut active_idx, &names) {
+        if names[active_idx] != active {
+            switch_to = Some(names[active_idx].clone());
+        }
+    }
+
+    let mut profile_input = PROFILE_INPUT.lock().unwrap();
+    ui.set_next_item_width(200.0);
+    InputText::new(ui, "##new_profile", &mut *profile_input)
+        .hint("Profile name")
+        .build();
+    ui.same_line();
+    if ui.button("New Profile") {
+        let name = profile_input.trim().to_string();
+        if !name.is_empty() && !names.contains(&name) {
+            switch_to = Some(name);
+            profile_input.clear();
+        }
+    }
+    drop(profile_input);
+
+    // The last profile can't be deleted, there always has to be an active one
+    if names.len() > 1 {
+        ui.same_line();
+        if ui.button("Delete Profile") {
+            if let Some(next) = names.iter().find(|n| **n != active).cloned() {
+                switch_profile(&next);
+                PROFILES.lock().unwrap().profiles.remove(&active);
+                save_config_to_file();
+                log::log(LogLevel::Info, "SYSTEM", &format!("Deleted profile: {}", active));
+            }
+        }
+    }
+
+    if let Some(name) = switch_to {
+        switch_profile(&name);
+    }
+}
+
+// Draws one launch entry; structural changes are queued in `pending_updates`.
+// Returns true if the entry itself was edited and the config needs saving.
+// `programs` lists (name, display name) of every entry, for the launch order combo.
+fn render_launch_row(
+    ui: &Ui,
+    prog: &mut ProgramToLaunch,
+    programs: &[(String, String)],
+    pending_updates: &mut Vec<PendingUpdate>,
+) -> bool {
+    let mut config_changed = false;
+
+    {
+        // The buffer only exists while an edit is pending, otherwise the current path is shown
+        let mut path_inputs = PATH_INPUTS.lock().unwrap();
+        let mut path_input = path_inputs.get(&prog.name).cloned().unwrap_or_else(|| prog.path.clone());
+        ui.set_next_item_width(400.0);
+        if InputText::new(ui, &format!("##path{}", prog.name), &mut path_input).build() {
+            path_inputs.insert(prog.name.clone(), path_input.clone());
+        }
+        if ui.is_item_hovered() {
+            if let Some((exe_path, _)) = get_program_command(prog).filter(|_| !is_url(&prog.path)) {
+                ui.tooltip_text(format!("{}\n{}", exe_path, exe_info_text(&exe_path)));
+            }
+        }
+        if path_inputs.contains_key(&prog.name) {
+            ui.same_line();
+            if ui.small_button(&format!("Apply##path{}", prog.name)) {
+                path_inputs.remove(&prog.name);
+                if path_input.trim() != prog.path {
+                    pending_updates.push(PendingUpdate {
+                        name: prog.name.clone(),
+                        action: UpdateAction::SetPath(path_input.trim().to_string()),
+                    });
+                    config_changed = true;
+                }
+            }
+        }
+    }
+    ui.same_line();
+    if ui.small_button(&format!("-##launch{}", prog.name)) {
+        pending_updates.push(PendingUpdate {
+            name: prog.name.clone(),
+            action: UpdateAction::Remove,
+        });
+        config_changed = true;
+        return config_changed; // Skip other UI elements for items being removed
+    }
+    ui.same_line();
+    if ui.small_button(&format!("Launch##launch_now{}", prog.name)) {
+        pending_updates.push(PendingUpdate {
+            name: prog.name.clone(),
+            action: UpdateAction::Launch,
+        });
+    }
+    if get_document_path(&prog.path).is_none() {
+        if let Some(process_name) = get_program_process_name(prog) {
+            ui.same_line();
+            if is_process_running_cached(&process_name) {
+                ui.text_colored([0.2, 0.8, 0.2, 1.0], "Running");
+            } else {
+                ui.text_colored([0.6, 0.6, 0.6, 1.0], "Stopped");
+            }
+            ui.same_line();
+            let limit = prog
+                .memory_warn_mb
+                .map_or_else(String::new, |mb| format!(" / {} MB", mb));
+            match process_usage(&prog.name, &process_name) {
+                Some((cpu, memory)) if MEMORY_EXCEEDED.lock().unwrap().contains(&prog.name) => ui.text_colored(
+                    [0.9, 0.3, 0.3, 1.0],
+                    format!("CPU {:.1}%  RAM {}{}", cpu, format_memory(memory), limit),
+                ),
+                Some((cpu, memory)) => {
+                    ui.text(format!("CPU {:.1}%  RAM {}{}", cpu, format_memory(memory), limit))
+                }
+                None => ui.text_colored([0.6, 0.6, 0.6, 1.0], format!("CPU -  RAM -{}", limit)),
+            }
+        }
+        if let Some(exit_code) = EXIT_CODES.lock().unwrap().get(&prog.name) {
+            ui.same_line();
+            match exit_code {
+                None => ui.text_colored([0.6, 0.6, 0.6, 1.0], "(last launch still running)"),
+                Some(0) => ui.text_colored([0.2, 0.8, 0.2, 1.0], "last exit: 0"),
+                Some(code) => ui.text_colored([0.9, 0.3, 0.3, 1.0], format!("last exit: {}", code)),
+            }
+        }
+        if let Some(secs) = prog.auto_close_after_secs {
+            ui.same_line();
+            ui.text_colored([0.6, 0.6, 0.6, 1.0], format!("closes after {}", format_run_time(secs)));
+        }
+        ui.same_line();
+        ui.text_colored(
+            [0.6, 0.6, 0.6, 1.0],
+            format!("{} launch{}", prog.launch_count, if prog.launch_count == 1 { "" } else { "es" }),
+        );
+        ui.same_line();
+        if ui.small_button(&format!("Kill##kill_launch{}", prog.name)) {
+            pending_updates.push(PendingUpdate {
+                name: prog.name.clone(),
+                action: UpdateAction::Kill,
+            });
+        }
+        ui.same_line();
+        if ui.small_button(&format!("Restart##restart{}", prog.name)) {
+            pending_updates.push(PendingUpdate {
+                name: prog.name.clone(),
+                action: UpdateAction::Restart,
+            });
+        }
+        // Elevated launches hand us no PID to close afterwards
+        if !prog.run_as_admin {
+            ui.same_line();
+            if ui.small_button(&format!("Test##test{}", prog.name)) {
+                pending_updates.push(PendingUpdate {
+                    name: prog.name.clone(),
+                    action: UpdateAction::TestLaunch,
+                });
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Launch now and close it again after the test time from General Settings");
+            }
+        }
+        if let Some(process_name) = get_program_process_name(prog) {
+            if let Some(result) = TEST_LAUNCHES.lock().unwrap().get(&prog.name) {
+                ui.same_line();
+                render_test_launch_result(ui, result, &process_name);
+            }
+        }
+    }
+    ui.same_line();
+    if ui.small_button(&format!("Duplicate##dup{}", prog.name)) {
+        pending_updates.push(PendingUpdate {
+            name: prog.name.clone(),
+            action: UpdateAction::Duplicate,
+        });
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.small_button(&format!("Copy##copy{}", prog.name)) {
+        ui.set_clipboard_text(&prog.path);
+        *LAST_COPIED.lock().unwrap() = Some((prog.name.clone(), Instant::now()));
+    }
+    if ui.is_item_hovered() {
+        let just_copied = LAST_COPIED
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|(name, at)| *name == prog.name && at.elapsed() < Duration::from_secs(1));
+        ui.tooltip_text(if just_copied { "Copied!" } else { "Copy the full command" });
+    }
+    ui.same_line();
+    if ui.small_button(&format!("Export Code##export{}", prog.name)) {
+        match encode_program_code(prog) {
+            Ok(code) => {
+                ui.set_clipboard_text(&code);
+                *LAST_COPIED.lock().unwrap() = Some((format!("{}#code", prog.name), Instant::now()));
+            }
+            Err(e) => log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("Failed to export {}: {}", prog.display_name, e),
+            ),
+        }
+    }
+    if ui.is_item_hovered() {
+        let copied_key = format!("{}#code", prog.name);
+        let just_copied = LAST_COPIED
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|(name, at)| *name == copied_key && at.elapsed() < Duration::from_secs(1));
+        ui.tooltip_text(if just_copied { "Copied!" } else { "Copy a code others can import this program from" });
+    }
+    if !is_url(&prog.path) {
+        ui.same_line();
+        if ui.small_button(&format!("Open Folder##folder{}", prog.name)) {
+            open_containing_folder(prog);
+        }
+    }
+    ui.same_line();
+    let star_label = if prog.is_favorite { "Unstar" } else { "Star" };
+    if ui.small_button(&format!("{}##favorite{}", star_label, prog.name)) {
+        pending_updates.push(PendingUpdate {
+            name: prog.name.clone(),
+            action: UpdateAction::SetFavorite(!prog.is_favorite),
+        });
+        config_changed = true;
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Favorites stay at the top of this list and of Quick Access");
+    }
+    ui.same_line();
+    let mut enabled = prog.enabled;
+    if ui.checkbox(&format!("Enabled##{}", prog.name), &mut enabled) {
+        pending_updates.push(PendingUpdate {
+            name: prog.name.clone(),
+            action: UpdateAction::SetEnabled(enabled),
+        });
+        config_changed = true;
+    }
+    {
+        let mut launch_errors = LAUNCH_ERRORS.lock().unwrap();
+        if let Some(error) = launch_errors.get(&prog.name) {
+            ui.text_colored([0.9, 0.3, 0.3, 1.0], error);
+            ui.same_line();
+            if ui.small_button(&format!("x##dismiss_error{}", prog.name)) {
+                launch_errors.remove(&prog.name);
+            }
+        }
+    }
+    // Popped when the token drops at the end of the row
+    let _disabled_style = (!prog.enabled).then(|| ui.push_style_var(StyleVar::Alpha(0.5)));
+
+    let mut display_name = prog.display_name.clone();
+    ui.set_next_item_width(200.0);
+    if InputText::new(ui, &format!("Display Name##{}", prog.name), &mut display_name).build() {
+        if display_name != prog.display_name && !display_name.trim().is_empty() {
+            pending_updates.push(PendingUpdate {
+                name: prog.name.clone(),
+                action: UpdateAction::UpdateDisplayName(display_name),
+            });
+            config_changed = true;
+        }
+    }
+    ui.same_line();
+    let mut group = prog.group.clone().unwrap_or_default();
+    ui.set_next_item_width(150.0);
+    if InputText::new(ui, &format!("Group (Enter to apply)##{}", prog.name), &mut group)
+        .hint(DEFAULT_GROUP)
+        .enter_returns_true(true)
+        .build()
+    {
+        let group = group.trim();
+        prog.group = (!group.is_empty() && group != DEFAULT_GROUP).then(|| group.to_string());
+        config_changed = true;
+    }
+
+    let shown_args: Vec<String> = prog
+        .args
+        .iter()
+        .enumerate()
+        .map(|(index, arg)| if prog.sensitive_args.contains(&index) { MASKED_ARG.to_string() } else { arg.clone() })
+        .collect();
+    let mut args_input = join_arguments(&shown_args);
+    ui.set_next_item_width(300.0);
+    if InputText::new(ui, &format!("Arguments (Enter to apply)##{}", prog.name), &mut args_input)
+        .enter_returns_true(true)
+        .build()
+    {
+        if prog.exe_path.is_empty() {
+            // Entry was never split, take the executable from the combined path
+            if let Some((exe_path, _)) = get_executable_and_args_from_command(&prog.path) {
+                prog.exe_path = exe_path;
+            }
+        }
+        if !prog.exe_path.is_empty() {
+            // A mask left in place keeps the sensitive value it stands for
+            let old_args = std::mem::take(&mut prog.args);
+            prog.args = split_arguments(&args_input)
+                .into_iter()
+                .enumerate()
+                .map(|(index, arg)| match old_args.get(index) {
+                    Some(old) if arg == MASKED_ARG && prog.sensitive_args.contains(&index) => old.clone(),
+                    _ => arg,
+                })
+                .collect();
+            let arg_count = prog.args.len();
+            prog.sensitive_args.retain(|&index| index < arg_count);
+            prog.path = masked_command(prog);
+            config_changed = true;
+        }
+    }
+    if !prog.exe_path.is_empty() && !prog.args.is_empty() {
+        ui.text_colored([0.6, 0.6, 0.6, 1.0], "Sensitive:");
+        for index in 0..prog.args.len() {
+            ui.same_line();
+            let mut sensitive = prog.sensitive_args.contains(&index);
+            if ui.checkbox(&format!("{}##sensitive_{}_{}", index + 1, index, prog.name), &mut sensitive) {
+                if sensitive {
+                    prog.sensitive_args.insert(index);
+                } else {
+                    prog.sensitive_args.remove(&index);
+                }
+                prog.path = masked_command(prog);
+                config_changed = true;
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Hide this argument and store it encrypted for your Windows user");
+            }
+        }
+        let mut sensitive_indices: Vec<usize> = prog.sensitive_args.iter().copied().collect();
+        sensitive_indices.sort();
+        for index in sensitive_indices {
+            let mut value = prog.args[index].clone();
+            ui.set_next_item_width(200.0);
+            if InputText::new(ui, &format!("Argument {} (Enter to apply)##secret_{}_{}", index + 1, index, prog.name), &mut value)
+                .password(true)
+                .enter_returns_true(true)
+                .build()
+            {
+                prog.args[index] = value;
+                config_changed = true;
+            }
+        }
+    }
+
+    let mut show_qa = prog.show_in_quick_access;
+    if ui.checkbox(&format!("Show in Quick Access##{}", prog.name), &mut show_qa) {
+        if show_qa != prog.show_in_quick_access {
+            pending_updates.push(PendingUpdate {
+                name: prog.name.clone(),
+                action: UpdateAction::ToggleQuickAccess(show_qa),
+            });
+            config_changed = true;
+        }
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Close on unload##{}", prog.name), &mut prog.close_on_unload) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Close gracefully##{}", prog.name), &mut prog.graceful_shutdown) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Kill child processes##{}", prog.name), &mut prog.kill_children) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Only kill own instances##{}", prog.name), &mut prog.only_kill_own_instances) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Match full path##{}", prog.name), &mut prog.match_full_path) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Run as administrator##{}", prog.name), &mut prog.run_as_admin) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Keep alive##{}", prog.name), &mut prog.keep_alive) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Dependents wait until running##{}", prog.name), &mut prog.wait_for_ready) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Capture output to log##{}", prog.name), &mut prog.capture_output) {
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.checkbox(&format!("Once per game session##{}", prog.name), &mut prog.launch_once_per_session) {
+        config_changed = true;
+    }
+
+    let state_labels: Vec<&str> = WINDOW_STATES.iter().map(|(_, label)| *label).collect();
+    let mut state_idx = WINDOW_STATES
+        .iter()
+        .position(|(state, _)| *state == prog.window_state)
+        .unwrap_or(0);
+    ui.set_next_item_width(120.0);
+    if ui.combo_simple_string(&format!("Window##{}", prog.name), &mut state_idx, &state_labels) {
+        prog.window_state = WINDOW_STATES[state_idx].0;
+        config_changed = true;
+    }
+    ui.same_line();
+    let priority_labels: Vec<&str> = PROCESS_PRIORITIES.iter().map(|(_, label)| *label).collect();
+    let mut priority_idx = PROCESS_PRIORITIES
+        .iter()
+        .position(|(priority, _)| *priority == prog.priority)
+        .unwrap_or(2);
+    ui.set_next_item_width(120.0);
+    if ui.combo_simple_string(&format!("Priority##{}", prog.name), &mut priority_idx, &priority_labels) {
+        prog.priority = PROCESS_PRIORITIES[priority_idx].0;
+        config_changed = true;
+    }
+    ui.same_line();
+    let running_labels: Vec<&str> = ALREADY_RUNNING_ACTIONS.iter().map(|(_, label)| *label).collect();
+    let mut running_idx = ALREADY_RUNNING_ACTIONS
+        .iter()
+        .position(|(action, _)| *action == prog.on_already_running)
+        .unwrap_or(0);
+    ui.set_next_item_width(180.0);
+    if ui.combo_simple_string(&format!("If already running##{}", prog.name), &mut running_idx, &running_labels) {
+        prog.on_already_running = ALREADY_RUNNING_ACTIONS[running_idx].0;
+        config_changed = true;
+    }
+    ui.same_line();
+    let candidates: Vec<&(String, String)> = programs.iter().filter(|(name, _)| *name != prog.name).collect();
+    let mut after_labels: Vec<&str> = vec!["(none)"];
+    after_labels.extend(candidates.iter().map(|(_, display_name)| display_name.as_str()));
+    let mut after_idx = prog
+        .launch_after
+        .as_ref()
+        .and_then(|dep| candidates.iter().position(|(name, _)| name == dep))
+        .map_or(0, |idx| idx + 1);
+    ui.set_next_item_width(180.0);
+    if ui.combo_simple_string(&format!("Launch after##{}", prog.name), &mut after_idx, &after_labels) {
+        prog.launch_after = after_idx.checked_sub(1).map(|idx| candidates[idx].0.clone());
+        config_changed = true;
+    }
+
+    let cores = logical_core_count();
+    let mut pin_cores = prog.cpu_affinity.is_some();
+    if ui.checkbox(&format!("Only run on some CPU cores##{}", prog.name), &mut pin_cores) {
+        prog.cpu_affinity = pin_cores.then(|| clamp_affinity_mask(u64::MAX, cores).unwrap_or(1));
+        config_changed = true;
+    }
+    if let Some(mask) = prog.cpu_affinity {
+        let mut new_mask = mask;
+        for core in 0..cores {
+            if core % 16 != 0 {
+                ui.same_line();
+            }
+            let mut selected = mask & (1 << core) != 0;
+            if ui.checkbox(&format!("{}##core_{}_{}", core, core, prog.name), &mut selected) {
+                new_mask ^= 1 << core;
+            }
+        }
+        // At least one core has to stay selected
+        if new_mask != mask && clamp_affinity_mask(new_mask, cores).is_some() {
+            prog.cpu_affinity = Some(new_mask);
+            config_changed = true;
+        }
+    }
+
+    // Elevated launches and documents start through the shell, which gives us no process to watch
+    if prog.run_as_admin || get_document_path(&prog.path).is_some() {
+        ui.text_disabled("Ready timeout: n/a");
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Only works for programs started directly, not as administrator or as a document");
+        }
+    } else {
+        let mut ready_timeout = prog.ready_timeout_secs.unwrap_or(0) as i32;
+        ui.set_next_item_width(100.0);
+        if ui
+            .input_int(&format!("Ready timeout (s, 0 = off)##{}", prog.name), &mut ready_timeout)
+            .build()
+        {
+            let ready_timeout = ready_timeout.max(0) as u64;
+            prog.ready_timeout_secs = (ready_timeout > 0).then_some(ready_timeout);
+            config_changed = true;
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Kills the program if it shows no window in time; launch retries also cover this");
+        }
+    }
+    ui.same_line();
+    let mut auto_close_mins = prog.auto_close_after_secs.map_or(0, |secs| secs / 60) as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int(&format!("Auto-close after (min, 0 = off)##{}", prog.name), &mut auto_close_mins)
+        .build()
+    {
+        let auto_close_mins = auto_close_mins.max(0) as u64;
+        prog.auto_close_after_secs = (auto_close_mins > 0).then_some(auto_close_mins * 60);
+        config_changed = true;
+    }
+
+    let mut memory_warn_mb = prog.memory_warn_mb.unwrap_or(0) as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int(&format!("Memory warning (MB, 0 = off)##{}", prog.name), &mut memory_warn_mb)
+        .step(100)
+        .build()
+    {
+        let memory_warn_mb = memory_warn_mb.max(0) as u64;
+        prog.memory_warn_mb = (memory_warn_mb > 0).then_some(memory_warn_mb);
+        config_changed = true;
+    }
+    if prog.memory_warn_mb.is_some() {
+        ui.same_line();
+        if ui.checkbox(&format!("Close when over##memkill{}", prog.name), &mut prog.kill_on_memory_exceed) {
+            config_changed = true;
+        }
+    }
+    ui.same_line();
+    let mut launch_delay_secs = prog.launch_delay_ms as f32 / 1000.0;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_float(&format!("Start delay (s)##{}", prog.name), &mut launch_delay_secs)
+        .build()
+    {
+        prog.launch_delay_ms = (launch_delay_secs.max(0.0) * 1000.0) as u64;
+        config_changed = true;
+    }
+
+    let mut launch_retries = prog.launch_retries as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int(&format!("Retries on failed launch##{}", prog.name), &mut launch_retries)
+        .build()
+    {
+        prog.launch_retries = launch_retries.max(0) as u32;
+        config_changed = true;
+    }
+    if prog.launch_retries > 0 {
+        ui.same_line();
+        let mut retry_delay_secs = prog.retry_delay_ms as f32 / 1000.0;
+        ui.set_next_item_width(100.0);
+        if ui
+            .input_float(&format!("Retry delay (s)##{}", prog.name), &mut retry_delay_secs)
+            .build()
+        {
+            prog.retry_delay_ms = (retry_delay_secs.max(0.0) * 1000.0) as u64;
+            config_changed = true;
+        }
+    }
+
+    ui.text_colored(
+        [0.6, 0.6, 0.6, 1.0],
+        format!(
+            "Working dir: {}",
+            prog.working_dir.as_deref().unwrap_or("(executable folder)")
+        ),
+    );
+    ui.same_line();
+    if ui.small_button(&format!("Browse##workdir{}", prog.name)) {
+        if let Some(folder) = FileDialog::new().pick_folder() {
+            prog.working_dir = Some(folder.to_string_lossy().to_string());
+            config_changed = true;
+        }
+    }
+    if prog.working_dir.is_some() {
+        ui.same_line();
+        if ui.small_button(&format!("Reset##workdir{}", prog.name)) {
+            prog.working_dir = None;
+            config_changed = true;
+        }
+    }
+
+    ui.text_colored(
+        [0.6, 0.6, 0.6, 1.0],
+        format!(
+            "Icon: {}",
+            prog.icon_override.as_deref().unwrap_or("(from program)")
+        ),
+    );
+    ui.same_line();
+    if ui.small_button(&format!("Choose Icon...##icon{}", prog.name)) {
+        if let Some(path) = FileDialog::new()
+            .add_filter("Image", &["png", "ico", "jpg", "jpeg"])
+            .pick_file()
+        {
+            pending_updates.push(PendingUpdate {
+                name: prog.name.clone(),
+                action: UpdateAction::SetIconOverride(Some(path.to_string_lossy().to_string())),
+            });
+            config_changed = true;
+        }
+    }
+    ui.same_line();
+    if ui.small_button(&format!("Refresh Icon##icon{}", prog.name)) {
+        pending_updates.push(PendingUpdate {
+            name: prog.name.clone(),
+            action: UpdateAction::RefreshIcon,
+        });
+    }
+    if prog.icon_override.is_some() {
+        ui.same_line();
+        if ui.small_button(&format!("Reset##icon{}", prog.name)) {
+            pending_updates.push(PendingUpdate {
+                name: prog.name.clone(),
+                action: UpdateAction::SetIconOverride(None),
+            });
+            config_changed = true;
+        }
+    }
+
+    if let Some(_env_node) = TreeNode::new(format!("Environment ({})##env{}", prog.env.len(), prog.name)).push(ui) {
+        let mut keys: Vec<String> = prog.env.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            ui.text(&key);
+            ui.same_line();
+            if let Some(value) = prog.env.get_mut(&key) {
+                ui.set_next_item_width(200.0);
+                if InputText::new(ui, &format!("##envval{}{}", prog.name, key), value).build() {
+                    config_changed = true;
+                }
+            }
+            ui.same_line();
+            if ui.small_button(&format!("-##env{}{}", prog.name, key)) {
+                prog.env.remove(&key);
+                config_changed = true;
+            }
+        }
+
+        let mut env_inputs = ENV_INPUTS.lock().unwrap();
+        let (new_key, new_value) = env_inputs.entry(prog.name.clone()).or_default();
+        ui.set_next_item_width(120.0);
+        InputText::new(ui, &format!("##envkey{}", prog.name), new_key)
+            .hint("KEY")
+            .build();
+        ui.same_line();
+        ui.set_next_item_width(200.0);
+        InputText::new(ui, &format!("##envnewval{}", prog.name), new_value)
+            .hint("value")
+            .build();
+        ui.same_line();
+        // Empty keys are never stored, Command would reject them anyway
+        if ui.small_button(&format!("+##envadd{}", prog.name)) && !new_key.trim().is_empty() {
+            prog.env.insert(new_key.trim().to_string(), std::mem::take(new_value));
+            new_key.clear();
+            config_changed = true;
+        }
+    }
+
+    for (i, (trigger, label)) in TRIGGER_OPTIONS.iter().enumerate() {
+        if i > 0 {
+            ui.same_line();
+        }
+        let mut enabled = prog.triggers.contains(trigger);
+        if ui.checkbox(&format!("{}##{}", label, prog.name), &mut enabled) {
+            if enabled {
+                prog.triggers.insert(*trigger);
+                if uses_keybind(prog) {
+                    register_program_keybind(prog);
+                }
+            } else {
+                prog.triggers.remove(trigger);
+            }
+            config_changed = true;
+        }
+    }
+
+    let mut enter_map_id = map_enter_trigger(prog).unwrap_or(0) as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int(&format!("Launch on entering map ID (0 = off)##{}", prog.name), &mut enter_map_id)
+        .build()
+    {
+        prog.triggers.retain(|t| !matches!(t, LaunchTrigger::OnMapEnter(_)));
+        if enter_map_id > 0 {
+            prog.triggers.insert(LaunchTrigger::OnMapEnter(enter_map_id as u32));
+        }
+        config_changed = true;
+    }
+    ui.same_line();
+    let mut leave_map_id = map_leave_trigger(prog).unwrap_or(0) as i32;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_int(&format!("Close on leaving map ID (0 = off)##{}", prog.name), &mut leave_map_id)
+        .build()
+    {
+        prog.triggers.retain(|t| !matches!(t, LaunchTrigger::OnMapLeave(_)));
+        if leave_map_id > 0 {
+            prog.triggers.insert(LaunchTrigger::OnMapLeave(leave_map_id as u32));
+        }
+        config_changed = true;
+    }
+    if let Some(map) = current_map() {
+        ui.same_line();
+        ui.text_colored([0.6, 0.6, 0.6, 1.0], format!("Current map: {}", map.map_id));
+    }
+
+    let mode_labels: Vec<&str> = GAME_MODES.iter().map(|(_, label)| *label).collect();
+    let mut mode_idx = GAME_MODES
+        .iter()
+        .position(|(mode, _)| *mode == prog.active_in)
+        .unwrap_or(0);
+    ui.set_next_item_width(120.0);
+    if ui.combo_simple_string(&format!("Only active in##{}", prog.name), &mut mode_idx, &mode_labels) {
+        prog.active_in = GAME_MODES[mode_idx].0;
+        config_changed = true;
+    }
+    if prog.active_in != GameMode::Any {
+        ui.same_line();
+        if ui.checkbox(&format!("Close when leaving the mode##{}", prog.name), &mut prog.close_outside_mode) {
+            config_changed = true;
+        }
+    }
+
+    let mut require_process = prog.require_process.clone().unwrap_or_default();
+    ui.set_next_item_width(150.0);
+    if InputText::new(ui, &format!("Only if running (Enter to apply)##{}", prog.name), &mut require_process)
+        .hint("e.g. Discord.exe")
+        .enter_returns_true(true)
+        .build()
+    {
+        let require_process = require_process.trim();
+        prog.require_process = (!require_process.is_empty()).then(|| require_process.to_string());
+        config_changed = true;
+    }
+    ui.same_line();
+    let mut skip_if_process = prog.skip_if_process.clone().unwrap_or_default();
+    ui.set_next_item_width(150.0);
+    if InputText::new(ui, &format!("Not if running (Enter to apply)##{}", prog.name), &mut skip_if_process)
+        .enter_returns_true(true)
+        .build()
+    {
+        let skip_if_process = skip_if_process.trim();
+        prog.skip_if_process = (!skip_if_process.is_empty()).then(|| skip_if_process.to_string());
+        config_changed = true;
+    }
+
+    let mut require_file = prog.require_file.clone().unwrap_or_default();
+    ui.set_next_item_width(300.0);
+    if InputText::new(ui, &format!("Only if file exists (Enter to apply)##{}", prog.name), &mut require_file)
+        .enter_returns_true(true)
+        .build()
+    {
+        let require_file = require_file.trim();
+        prog.require_file = (!require_file.is_empty()).then(|| require_file.to_string());
+        REQUIRE_FILE_WARNED.lock().unwrap().remove(&prog.name);
+        config_changed = true;
+    }
+    if prog.require_file.is_some() {
+        let mut message = prog.require_file_message.clone().unwrap_or_default();
+        ui.set_next_item_width(300.0);
+        if InputText::new(ui, &format!("Message if missing (Enter to apply)##{}", prog.name), &mut message)
+            .hint("e.g. Run the mod installer first")
+            .enter_returns_true(true)
+            .build()
+        {
+            let message = message.trim();
+            prog.require_file_message = (!message.is_empty()).then(|| message.to_string());
+            config_changed = true;
+        }
+    }
+
+    let mut pre_launch = prog.pre_launch.clone().unwrap_or_default();
+    ui.set_next_item_width(300.0);
+    if InputText::new(ui, &format!("Run before launch (Enter to apply)##{}", prog.name), &mut pre_launch)
+        .hint("e.g. cmd /c del \"%TEMP%\\app.lock\"")
+        .enter_returns_true(true)
+        .build()
+    {
+        let pre_launch = pre_launch.trim();
+        prog.pre_launch = (!pre_launch.is_empty()).then(|| pre_launch.to_string());
+        config_changed = true;
+    }
+    if prog.pre_launch.is_some() {
+        ui.same_line();
+        if ui.checkbox(&format!("Don't launch if it fails##{}", prog.name), &mut prog.abort_on_hook_failure) {
+            config_changed = true;
+        }
+    }
+
+    let mut post_kill = prog.post_kill.clone().unwrap_or_default();
+    ui.set_next_item_width(300.0);
+    if InputText::new(ui, &format!("Run after closing (Enter to apply)##{}", prog.name), &mut post_kill)
+        .enter_returns_true(true)
+        .build()
+    {
+        let post_kill = post_kill.trim();
+        prog.post_kill = (!post_kill.is_empty()).then(|| post_kill.to_string());
+        config_changed = true;
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text(format!(
+            "Commands run hidden and are stopped after {}s ({}s when the addon unloads)",
+            HOOK_TIMEOUT.as_secs(),
+            UNLOAD_HOOK_TIMEOUT.as_secs()
+        ));
+    }
+
+    let mut precheck_url = prog.precheck_url.clone().unwrap_or_default();
+    ui.set_next_item_width(200.0);
+    if InputText::new(ui, &format!("Only if reachable (Enter to apply)##{}", prog.name), &mut precheck_url)
+        .hint("e.g. localhost:8080")
+        .enter_returns_true(true)
+        .build()
+    {
+        let precheck_url = precheck_url.trim();
+        if !precheck_url.is_empty() && precheck_address(precheck_url).is_none() {
+            log::log(
+                LogLevel::Warning,
+                "SYSTEM",
+                &format!("{} is not a host:port or http(s) URL", precheck_url),
+            );
+        } else {
+            prog.precheck_url = (!precheck_url.is_empty()).then(|| precheck_url.to_string());
+            config_changed = true;
+        }
+    }
+    
+    if uses_keybind(prog) {
+        ui.text_colored(
+            [0.6, 0.6, 0.6, 1.0],
+            format!("Keybind ID: LAUNCH_{}", prog.name),
+        );
+        // Registered as the default binding and carried over if the ID ever changes
+        if InputText::new(ui, &format!("Default key (Enter to apply)##{}", prog.name), &mut prog.keybind)
+            .hint("e.g. ALT+SHIFT+1")
+            .enter_returns_true(true)
+            .build()
+        {
+            prog.keybind = prog.keybind.trim().to_uppercase();
+            register_program_keybind(prog);
+            config_changed = true;
+        }
+
+        ui.same_line();
+        let mut capturing = KEYBIND_CAPTURE.lock().unwrap();
+        if capturing.as_deref() == Some(prog.name.as_str()) {
+            ui.text_colored([0.9, 0.8, 0.2, 1.0], "Press a key combination (Esc to cancel)...");
+            let io = ui.io();
+            if io.keys_down[VK_ESCAPE] {
+                *capturing = None;
+            } else if let Some(combo) = captured_key_combination(ui) {
+                *capturing = None;
+                prog.keybind = combo;
+                register_program_keybind(prog);
+                log::log(
+                    LogLevel::Info,
+                    "SYSTEM",
+                    &format!("Default key of {} set to {}", prog.display_name, prog.keybind),
+                );
+                config_changed = true;
+            }
+        } else {
+            if ui.small_button(&format!("Rebind##{}", prog.name)) {
+                *capturing = Some(prog.name.clone());
+            }
+            if ui.is_item_hovered() {
+                // Nexus has no API to read a binding or open its editor for an identifier
+                ui.tooltip_text(
+                    "Sets the default key from the next key you press.\n\
+                     A binding changed in Nexus's own Keybinds menu takes precedence.",
+                );
+            }
+        }
+    }
+
+    let action_labels: Vec<&str> = QUICK_ACCESS_ACTIONS.iter().map(|(_, label)| *label).collect();
+    let mut action_idx = QUICK_ACCESS_ACTIONS
+        .iter()
+        .position(|(action, _)| *action == prog.quick_access_action)
+        .unwrap_or(0);
+    ui.set_next_item_width(200.0);
+    if ui.combo_simple_string(&format!("Quick Access / keybind action##{}", prog.name), &mut action_idx, &action_labels) {
+        prog.quick_access_action = QUICK_ACCESS_ACTIONS[action_idx].0;
+        config_changed = true;
+    }
+
+    if let Some(others) = DUPLICATE_PATHS.lock().unwrap().get(&prog.name) {
+        let others: Vec<&str> = others
+            .iter()
+            .map(|name| {
+                programs
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map_or(name.as_str(), |(_, display_name)| display_name.as_str())
+            })
+            .collect();
+        ui.text_colored(
+            [1.0, 0.8, 0.0, 1.0],
+            format!("Same executable as: {}", others.join(", ")),
+        );
+    }
+    ui.separator();
+    config_changed
+}
+
+fn render_programs_to_launch_section(ui: &Ui) {
+    if !ui.collapsing_header("Programs to Launch", TreeNodeFlags::DEFAULT_OPEN) {
+        return;
+    }
+    
+    let mut config_changed = false;
+    let mut pending_updates: Vec<PendingUpdate> = Vec::new();
+    let mut new_program_to_add: Option<ProgramToLaunch> = None;
+    let mut folder_to_add: Option<PathBuf> = None;
+    let mut imported_program: Option<ProgramToLaunch> = None;
+    let mut undo_remove = false;
+
+    {
+        let mut removed = REMOVED_PROGRAM.lock().unwrap();
+        match &*removed {
+            Some((program, _, at)) if at.elapsed() < UNDO_REMOVE_WINDOW => {
+                if ui.button(&format!("Undo remove of {}", program.display_name)) {
+                    undo_remove = true;
+                }
+            }
+            Some(_) => *removed = None,
+            None => {}
+        }
+    }
+
+    let filter = {
+        let mut filter = LAUNCH_FILTER.lock().unwrap();
+        ui.set_next_item_width(300.0);
+        InputText::new(ui, "##launch_filter", &mut *filter)
+            .hint("Search programs...")
+            .build();
+        filter.trim().to_lowercase()
+    };
+    ui.same_line();
+    if ui.button("Reset stats") {
+        for prog in CONFIG.lock().unwrap().programs_to_launch.iter_mut() {
+            prog.launch_count = 0;
+        }
+        config_changed = true;
+    }
+    ui.same_line();
+    if ui.button("Remove All Programs") {
+        *PENDING_DESTRUCTIVE_ACTION.lock().unwrap() = Some(DestructiveAction::RemoveAllPrograms);
+    }
+    let mut sort_request: Option<bool> = None;
+    if ui.button("Sort A-Z") {
+        sort_request = Some(false);
+    }
+    ui.same_line();
+    if ui.button("Sort by trigger") {
+        sort_request = Some(true);
     }
-    
-    let mut config_changed = false;
-    let mut pending_updates: Vec<PendingUpdate> = Vec::new();
-    let mut new_program_to_add: Option<ProgramToLaunch> = None;
     
     // First pass: collect UI changes without holding lock for too long
     {
         let mut config = CONFIG.lock().unwrap();
         
-        for prog in config.programs_to_launch.iter_mut() {
-            ui.text(&prog.path);
-            ui.same_line();
-            if ui.small_button(&format!("-##launch{}", prog.name)) {
-                pending_updates.push(PendingUpdate {
-                    name: prog.name.clone(),
-                    action: UpdateAction::Remove,
-                });
-                config_changed = true;
-                continue; // Skip other UI elements for items being removed
+        // Ungrouped programs come first, then the user's groups alphabetically
+        let mut groups: Vec<String> = config
+            .programs_to_launch
+            .iter()
+            .map(|p| group_label(p).to_string())
+            .filter(|g| g != DEFAULT_GROUP)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        groups.sort();
+        groups.insert(0, DEFAULT_GROUP.to_string());
+
+        let program_names: Vec<(String, String)> = config
+            .programs_to_launch
+            .iter()
+            .map(|p| (p.name.clone(), p.display_name.clone()))
+            .collect();
+
+        for (key, names) in keybind_conflicts(&config.programs_to_launch) {
+            ui.text_colored(
+                [1.0, 0.8, 0.0, 1.0],
+                format!("{} is the default key of several programs: {}", key, names.join(", ")),
+            );
+        }
+
+        for group in &groups {
+            let matches_filter = |prog: &ProgramToLaunch| {
+                filter.is_empty()
+                    || prog.display_name.to_lowercase().contains(&filter)
+                    || prog.path.to_lowercase().contains(&filter)
+            };
+            if !config
+                .programs_to_launch
+                .iter()
+                .any(|p| group_label(p) == group && matches_filter(p))
+            {
+                continue;
+            }
+            if !ui.collapsing_header(&format!("{}##launch_group_{}", group, group), TreeNodeFlags::DEFAULT_OPEN) {
+                continue;
             }
 
-            let mut display_name = prog.display_name.clone();
-            ui.set_next_item_width(200.0);
-            if InputText::new(ui, &format!("Display Name##{}", prog.name), &mut display_name).build() {
-                if display_name != prog.display_name && !display_name.trim().is_empty() {
-                    pending_updates.push(PendingUpdate {
-                        name: prog.name.clone(),
-                        action: UpdateAction::UpdateDisplayName(display_name),
-                    });
-                    config_changed = true;
+            for prog in config.programs_to_launch.iter_mut() {
+                if group_label(prog) != group || !matches_filter(prog) {
+                    continue;
                 }
-            }
 
-            let mut show_qa = prog.show_in_quick_access;
-            if ui.checkbox(&format!("Show in Quick Access##{}", prog.name), &mut show_qa) {
-                if show_qa != prog.show_in_quick_access {
-                    pending_updates.push(PendingUpdate {
-                        name: prog.name.clone(),
-                        action: UpdateAction::ToggleQuickAccess(show_qa),
-                    });
+                if render_launch_row(ui, prog, &program_names, &mut pending_updates) {
                     config_changed = true;
                 }
             }
-            ui.same_line();
-            if ui.checkbox(&format!("Close on unload##{}", prog.name), &mut prog.close_on_unload) {
-                config_changed = true;
-            }
-
-            if ui.radio_button_bool(
-                &format!("On Addon Start##{}", prog.name),
-                prog.trigger == LaunchTrigger::OnAddonLoad,
-            ) { 
-                prog.trigger = LaunchTrigger::OnAddonLoad; 
-                config_changed = true; 
-            }
-            ui.same_line();
-            if ui.radio_button_bool(
-                &format!("On Keybind##{}", prog.name),
-                prog.trigger == LaunchTrigger::OnKeybind,
-            ) { 
-                prog.trigger = LaunchTrigger::OnKeybind; 
-                config_changed = true; 
-            }
-            
-            if prog.trigger == LaunchTrigger::OnKeybind {
-                ui.text_colored(
-                    [0.6, 0.6, 0.6, 1.0],
-                    format!("Keybind ID: LAUNCH_{}", prog.name),
-                );
-            }
-            ui.separator();
         }
         
         // Handle new program addition UI
         ui.text("Add new program:");
+        ui.text_colored(
+            [0.6, 0.6, 0.6, 1.0],
+            "Executables, documents and URLs (https://..., steam://...) are accepted.",
+        );
+        ui.text_colored(
+            [0.6, 0.6, 0.6, 1.0],
+            format!(
+                "Paths and arguments may use %ENV_VARS% and {}",
+                PATH_TOKENS
+                    .iter()
+                    .map(|(token, meaning)| format!("{} ({})", token, meaning))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        );
         let mut launch_input = LAUNCH_INPUT.lock().unwrap();
         ui.group(|| {
             ui.set_next_item_width(300.0);
@@ -785,7 +7300,9 @@ fn render_programs_to_launch_section(ui: &Ui) {
             ui.same_line();
             if ui.button("Browse...") {
                 if let Some(path) = FileDialog::new()
-                    .add_filter("Executable", &["exe"])
+                    .add_filter("Executable", &["exe", "lnk"])
+                    .add_filter("Scripts", &["bat", "cmd"])
+                    .add_filter("All files", &["*"])
                     .pick_file()
                 {
                     *launch_input = path.to_string_lossy().to_string();
@@ -795,28 +7312,60 @@ fn render_programs_to_launch_section(ui: &Ui) {
             if ui.button("+##add_launch_btn") && !launch_input.is_empty() {
                 let path = launch_input.clone();
                 if let Some(base_name) = get_program_name_from_command(&path) {
-                    let sanitized_base_name = sanitize_identifier(&base_name);
-                    let mut final_name = sanitized_base_name.clone();
-                    let mut suffix = 2;
-                    while config.programs_to_launch.iter().any(|p| p.name == final_name) {
-                        final_name = format!("{}_{}", sanitized_base_name, suffix);
-                        suffix += 1;
-                    }
+                    let final_name = unique_program_name(&sanitize_identifier(&base_name), &config.programs_to_launch);
 
-                    new_program_to_add = Some(ProgramToLaunch {
+                    let mut new_prog = ProgramToLaunch {
                         name: final_name,
                         display_name: base_name,
                         path,
-                        trigger: LaunchTrigger::OnAddonLoad,
-                        close_on_unload: false,
-                        show_in_quick_access: true,
-                    });
+                        ..Default::default()
+                    };
+                    split_program_command(&mut new_prog);
+                    new_program_to_add = Some(new_prog);
                     config_changed = true;
                 }
                 launch_input.clear();
             }
         });
+        if ui.button("Add from Folder...") {
+            folder_to_add = FileDialog::new().pick_folder();
+        }
+        ui.same_line();
+        let mut recursive = ADD_FOLDER_RECURSIVE.load(Ordering::Relaxed);
+        if ui.checkbox("Include subfolders", &mut recursive) {
+            ADD_FOLDER_RECURSIVE.store(recursive, Ordering::Relaxed);
+        }
+
+        let mut import_code = IMPORT_CODE_INPUT.lock().unwrap();
+        ui.set_next_item_width(300.0);
+        InputText::new(ui, "##import_code", &mut *import_code)
+            .hint("Paste an exported program code")
+            .build();
+        ui.same_line();
+        if ui.button("Import Code") && !import_code.trim().is_empty() {
+            match decode_program_code(&import_code) {
+                Ok(program) => {
+                    imported_program = Some(program);
+                    import_code.clear();
+                }
+                Err(e) => log::log(LogLevel::Warning, "SYSTEM", &format!("Not importing: {}", e)),
+            }
+        }
     } // Config lock is dropped here
+
+    // Any other change makes the stashed index meaningless, so undo is only offered
+    // until the list is touched again
+    let removing = pending_updates.iter().any(|u| matches!(u.action, UpdateAction::Remove));
+    if undo_remove {
+        undo_program_removal();
+    } else if (config_changed && !removing)
+        || new_program_to_add.is_some()
+        || sort_request.is_some()
+        || imported_program.is_some()
+        || folder_to_add.is_some()
+    {
+        *REMOVED_PROGRAM.lock().unwrap() = None;
+    }
     
     // Second pass: Process all updates safely
     for update in pending_updates {
@@ -826,12 +7375,159 @@ fn render_programs_to_launch_section(ui: &Ui) {
                 if let Some(pos) = config.programs_to_launch.iter().position(|p| p.name == update.name) {
                     let prog = config.programs_to_launch.remove(pos);
                     drop(config); // Release lock before UI operations
+                    *REMOVED_PROGRAM.lock().unwrap() = Some((prog.clone(), pos, Instant::now()));
                     
                     // Clean up UI elements
                     remove_quick_access(&format!("QA_ITEM_{}", prog.name));
                     teardown_quick_access_for_program(&prog);
                 }
             }
+            UpdateAction::SetIconOverride(icon_override) => {
+                let prog_to_update = {
+                    let mut config = CONFIG.lock().unwrap();
+                    if let Some(prog) = config.programs_to_launch.iter_mut().find(|p| p.name == update.name) {
+                        prog.icon_override = icon_override;
+                        Some(prog.clone())
+                    } else {
+                        None
+                    }
+                };
+
+                if let Some(prog) = prog_to_update {
+                    // Drop the cached PNG so it is regenerated from the new source
+                    refresh_program_icon(&prog);
+                }
+            }
+            UpdateAction::RefreshIcon => {
+                let prog = CONFIG
+                    .lock()
+                    .unwrap()
+                    .programs_to_launch
+                    .iter()
+                    .find(|p| p.name == update.name)
+                    .cloned();
+                if let Some(prog) = prog {
+                    refresh_program_icon(&prog);
+                }
+            }
+            UpdateAction::Kill => {
+                let target = CONFIG
+                    .lock()
+                    .unwrap()
+                    .programs_to_launch
+                    .iter()
+                    .find(|p| p.name == update.name)
+                    .and_then(KillTarget::for_program);
+                set_keep_alive_paused(&update.name, true);
+                if let Some(target) = target {
+                    // cleanup_processes keeps the Gw2-64.exe safety filter
+                    cleanup_processes_in_background(vec![target]);
+                }
+            }
+            UpdateAction::SetPath(new_path) => {
+                // Same check as validate_and_cleanup_config, so the entry survives the next load
+                let valid = is_url(&new_path)
+                    || get_executable_and_args_from_command(&new_path)
+                        .is_some_and(|(exe_path, _)| Path::new(&exe_path).exists());
+                if !valid {
+                    log::log(
+                        LogLevel::Warning,
+                        "SYSTEM",
+                        &format!("Not changing path, {} does not point at an existing file", new_path),
+                    );
+                    continue;
+                }
+
+                // `name` stays the same so the keybind and Quick Access IDs are kept
+                let prog_to_update = {
+                    let mut config = CONFIG.lock().unwrap();
+                    if let Some(prog) = config.programs_to_launch.iter_mut().find(|p| p.name == update.name) {
+                        let before = prog.clone();
+                        prog.path = new_path;
+                        prog.exe_path.clear();
+                        prog.args.clear();
+                        prog.sensitive_args.clear();
+                        split_program_command(prog);
+                        // The box shows sensitive arguments masked; a mask left in place keeps
+                        // the value it stands for, as in the Arguments box
+                        for (index, arg) in prog.args.iter_mut().enumerate() {
+                            if *arg == MASKED_ARG && before.sensitive_args.contains(&index) {
+                                if let Some(old) = before.args.get(index) {
+                                    *arg = old.clone();
+                                    prog.sensitive_args.insert(index);
+                                }
+                            }
+                        }
+                        if prog.exe_path.is_empty() && prog.path.contains(MASKED_ARG) {
+                            // Unsplit, the mask itself would be passed to the program
+                            log::log(
+                                LogLevel::Warning,
+                                "SYSTEM",
+                                &format!(
+                                    "Not changing path of {}: its masked arguments can't be kept, edit them in the Arguments box",
+                                    prog.display_name
+                                ),
+                            );
+                            *prog = before;
+                            None
+                        } else {
+                            if !prog.sensitive_args.is_empty() {
+                                prog.path = masked_command(prog);
+                            }
+                            Some(prog.clone())
+                        }
+                    } else {
+                        None
+                    }
+                };
+
+                if let Some(prog) = prog_to_update {
+                    log::log(
+                        LogLevel::Info,
+                        "SYSTEM",
+                        &format!("Changed path of {} to {}", prog.display_name, prog.path),
+                    );
+                    refresh_program_icon(&prog);
+                }
+            }
+            UpdateAction::Restart => {
+                let prog = CONFIG
+                    .lock()
+                    .unwrap()
+                    .programs_to_launch
+                    .iter()
+                    .find(|p| p.name == update.name)
+                    .cloned();
+                if let Some(prog) = prog {
+                    if let Some(process_name) = get_program_process_name(&prog) {
+                        restart_program(prog, process_name);
+                    }
+                }
+            }
+            UpdateAction::Launch => {
+                let prog = CONFIG
+                    .lock()
+                    .unwrap()
+                    .programs_to_launch
+                    .iter()
+                    .find(|p| p.name == update.name)
+                    .cloned();
+                if let Some(prog) = prog {
+                    launch_process(&prog);
+                }
+            }
+            UpdateAction::TestLaunch => {
+                let (prog, run_for) = {
+                    let config = CONFIG.lock().unwrap();
+                    let prog = config.programs_to_launch.iter().find(|p| p.name == update.name).cloned();
+                    (prog, Duration::from_secs(config.test_launch_secs))
+                };
+                if let Some(prog) = prog {
+                    if let Some(process_name) = get_program_process_name(&prog) {
+                        start_test_launch(&prog, process_name, run_for);
+                    }
+                }
+            }
             UpdateAction::UpdateDisplayName(new_display_name) => {
                 let prog_to_update = {
                     let mut config = CONFIG.lock().unwrap();
@@ -849,6 +7545,62 @@ fn render_programs_to_launch_section(ui: &Ui) {
                     setup_quick_access_for_program(&prog);
                 }
             }
+            UpdateAction::Duplicate => {
+                let copy = {
+                    let mut config = CONFIG.lock().unwrap();
+                    let copy = config
+                        .programs_to_launch
+                        .iter()
+                        .find(|p| p.name == update.name)
+                        .cloned()
+                        .map(|prog| ProgramToLaunch {
+                            name: unique_program_name(&prog.name, &config.programs_to_launch),
+                            display_name: format!("{} (copy)", prog.display_name),
+                            launch_count: 0,
+                            ..prog
+                        });
+                    if let Some(copy) = &copy {
+                        config.programs_to_launch.push(copy.clone());
+                    }
+                    copy
+                }; // Release lock before UI operations
+
+                if let Some(copy) = copy {
+                    if uses_keybind(&copy) || copy.show_in_quick_access {
+                        register_program_keybind(&copy);
+                    }
+                    setup_quick_access_for_program(&copy);
+                }
+            }
+            UpdateAction::SetFavorite(favorite) => {
+                reorder_programs(|programs| {
+                    if let Some(prog) = programs.iter_mut().find(|p| p.name == update.name) {
+                        prog.is_favorite = favorite;
+                    }
+                });
+            }
+            UpdateAction::SetEnabled(enabled) => {
+                let prog_to_update = {
+                    let mut config = CONFIG.lock().unwrap();
+                    if let Some(prog) = config.programs_to_launch.iter_mut().find(|p| p.name == update.name) {
+                        prog.enabled = enabled;
+                        Some(prog.clone())
+                    } else {
+                        None
+                    }
+                };
+
+                if let Some(prog) = prog_to_update {
+                    if enabled {
+                        if uses_keybind(&prog) || prog.show_in_quick_access {
+                            register_program_keybind(&prog);
+                        }
+                        setup_quick_access_for_program(&prog);
+                    } else {
+                        remove_quick_access(&format!("QA_ITEM_{}", prog.name));
+                    }
+                }
+            }
             UpdateAction::ToggleQuickAccess(show_qa) => {
                 let prog_to_update = {
                     let mut config = CONFIG.lock().unwrap();
@@ -863,6 +7615,7 @@ fn render_programs_to_launch_section(ui: &Ui) {
                 if let Some(prog) = prog_to_update {
                     // Update UI without holding config lock
                     if show_qa {
+                        register_program_keybind(&prog);
                         setup_quick_access_for_program(&prog);
                     } else {
                         remove_quick_access(&format!("QA_ITEM_{}", prog.name));
@@ -882,7 +7635,18 @@ fn render_programs_to_launch_section(ui: &Ui) {
         setup_quick_access_for_program(&new_prog);
     }
     
+    if let Some(by_trigger) = sort_request {
+        sort_programs(by_trigger);
+    }
+    if let Some(program) = imported_program {
+        add_programs(vec![program]);
+    }
+    if let Some(folder) = folder_to_add {
+        add_programs_from_folder(&folder, ADD_FOLDER_RECURSIVE.load(Ordering::Relaxed));
+    }
+    
     if config_changed {
+        refresh_duplicate_paths(&CONFIG.lock().unwrap().programs_to_launch);
         save_config_to_file();
     }
 }
@@ -893,9 +7657,9 @@ fn render_programs_to_kill_section(ui: &Ui) {
     }
     
     let mut changed = false;
-    let mut programs_to_kill = {
+    let (mut programs_to_kill, mut full_path_entries) = {
         let config = CONFIG.lock().unwrap();
-        config.programs_to_kill.clone()
+        (config.programs_to_kill.clone(), config.kill_full_path_entries.clone())
     }; // Release lock early
     
     let mut to_remove_idx = None;
@@ -906,15 +7670,35 @@ fn render_programs_to_kill_section(ui: &Ui) {
             to_remove_idx = Some(i);
             changed = true;
         }
+        // Only entries that are paths can be matched by path
+        if name.contains(['\\', '/']) {
+            ui.same_line();
+            let mut full_path = full_path_entries.contains(name);
+            if ui.checkbox(&format!("Match full path##kill{}", i), &mut full_path) {
+                if full_path {
+                    full_path_entries.insert(name.clone());
+                } else {
+                    full_path_entries.remove(name);
+                }
+                CONFIG.lock().unwrap().kill_full_path_entries = full_path_entries.clone();
+                changed = true;
+            }
+        }
     }
     
     if let Some(i) = to_remove_idx {
-        programs_to_kill.remove(i);
+        let removed = programs_to_kill.remove(i);
+        full_path_entries.remove(&removed);
         let mut config = CONFIG.lock().unwrap();
         config.programs_to_kill = programs_to_kill.clone();
+        config.kill_full_path_entries = full_path_entries;
     }
     
     ui.text("Add process name to kill list:");
+    ui.text_colored(
+        [0.6, 0.6, 0.6, 1.0],
+        "Wildcards are supported: * matches anything, ? a single character (e.g. *helper*.exe).",
+    );
     let mut kill_input = KILL_INPUT.lock().unwrap();
     ui.group(|| {
         ui.set_next_item_width(300.0);
@@ -929,13 +7713,123 @@ fn render_programs_to_kill_section(ui: &Ui) {
             }
             kill_input.clear();
         }
+        ui.same_line();
+        if ui.button("Pick running process...") {
+            *PROCESS_PICKER.lock().unwrap() = Some(ProcessPicker {
+                filter: String::new(),
+                processes: running_processes(),
+            });
+        }
     });
+
+    if ui.button("Preview kill list") {
+        *KILL_PREVIEW.lock().unwrap() = Some(preview_kill_list());
+    }
+    let mut kill_preview = KILL_PREVIEW.lock().unwrap();
+    let mut hide_preview = false;
+    if let Some(preview) = kill_preview.as_ref() {
+        ui.same_line();
+        hide_preview = ui.small_button("Hide##kill_preview");
+        if preview.is_empty() {
+            ui.text_colored([0.6, 0.6, 0.6, 1.0], "Nothing running would be closed on unload.");
+        } else {
+            ui.text("Running now and closed on unload:");
+            for entry in preview {
+                ui.bullet_text(entry);
+            }
+        }
+    }
+    if hide_preview {
+        *kill_preview = None;
+    }
+    drop(kill_preview);
+
+    ui.separator();
+    let mut timeout_secs = CONFIG.lock().unwrap().graceful_shutdown_timeout_ms as f32 / 1000.0;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_float("Graceful close timeout (s)##graceful_timeout", &mut timeout_secs)
+        .build()
+    {
+        CONFIG.lock().unwrap().graceful_shutdown_timeout_ms = (timeout_secs.max(0.0) * 1000.0) as u64;
+        changed = true;
+    }
+    let mut scan_ttl_secs = CONFIG.lock().unwrap().process_scan_ttl_ms as f32 / 1000.0;
+    ui.set_next_item_width(100.0);
+    if ui
+        .input_float("Process scan interval (s)##scan_ttl", &mut scan_ttl_secs)
+        .build()
+    {
+        let scan_ttl_ms = (scan_ttl_secs.max(0.0) * 1000.0) as u64;
+        CONFIG.lock().unwrap().process_scan_ttl_ms = scan_ttl_ms;
+        PROCESS_SCAN_TTL_MS.store(scan_ttl_ms, Ordering::Relaxed);
+        changed = true;
+    }
+    ui.text_colored(
+        [0.6, 0.6, 0.6, 1.0],
+        "Programs are asked to close (WM_CLOSE) before being force-killed.",
+    );
     
     if changed {
         save_config_to_file();
     }
 }
 
+fn render_protected_processes_section(ui: &Ui) {
+    if !ui.collapsing_header("Protected Processes", TreeNodeFlags::empty()) {
+        return;
+    }
+
+    ui.text_colored(
+        [0.6, 0.6, 0.6, 1.0],
+        "These are never killed, even if listed above or part of a killed process tree.",
+    );
+
+    let mut changed = false;
+    let mut protected = {
+        let config = CONFIG.lock().unwrap();
+        config.protected_processes.clone()
+    }; // Release lock early
+
+    let mut to_remove_idx = None;
+    for (i, name) in protected.iter().enumerate() {
+        ui.text(name);
+        // The game can't be unprotected
+        if !name.eq_ignore_ascii_case(GAME_PROCESS_NAME) {
+            ui.same_line();
+            if ui.small_button(&format!("-##protected{}", i)) {
+                to_remove_idx = Some(i);
+            }
+        }
+    }
+
+    if let Some(i) = to_remove_idx {
+        protected.remove(i);
+        changed = true;
+    }
+
+    ui.text("Add process name to protect:");
+    let mut protected_input = PROTECTED_INPUT.lock().unwrap();
+    ui.group(|| {
+        ui.set_next_item_width(300.0);
+        InputText::new(ui, "##add_protected", &mut *protected_input).build();
+        ui.same_line();
+        if ui.button("+##add_protected_btn") && !protected_input.trim().is_empty() {
+            let name = protected_input.trim().to_string();
+            if !protected.iter().any(|p| p.eq_ignore_ascii_case(&name)) {
+                protected.push(name);
+                changed = true;
+            }
+            protected_input.clear();
+        }
+    });
+
+    if changed {
+        CONFIG.lock().unwrap().protected_processes = protected;
+        save_config_to_file();
+    }
+}
+
 nexus::export! {
     name: "Assisted Deployment and Departure",
     signature: -128175,
@@ -944,4 +7838,294 @@ nexus::export! {
     unload,
     provider: UpdateProvider::GitHub,
     update_link: "https://github.com/qjv/assisted_deployment_and_departure"
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates an empty "<dir with spaces>/My App.exe" so the is_file checks succeed
+    // An empty "My App.exe" in its own temp folder, removed again when the test ends
+    struct FakeExe {
+        dir: PathBuf,
+        exe: PathBuf,
+    }
+
+    impl std::ops::Deref for FakeExe {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.exe
+        }
+    }
+
+    impl Drop for FakeExe {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    fn fake_exe(test_name: &str) -> FakeExe {
+        let dir = std::env::temp_dir().join(format!("add test {} {}", std::process::id(), test_name));
+        fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("My App.exe");
+        fs::write(&exe, b"").unwrap();
+        FakeExe { dir, exe }
+    }
+
+    #[test]
+    fn unquoted_path_with_spaces_without_args() {
+        let exe = fake_exe("no_args");
+        let command = exe.to_string_lossy().to_string();
+        assert_eq!(
+            get_executable_and_args_from_command(&command),
+            Some((command.clone(), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn unquoted_path_with_spaces_and_args() {
+        let exe = fake_exe("args");
+        let exe_str = exe.to_string_lossy().to_string();
+        let command = format!("{} --minimized --profile \"Raid Night\"", exe_str);
+        assert_eq!(
+            get_executable_and_args_from_command(&command),
+            Some((
+                exe_str,
+                vec!["--minimized".to_string(), "--profile".to_string(), "Raid Night".to_string()]
+            ))
+        );
+    }
+
+    #[test]
+    fn unquoted_path_with_exe_in_args() {
+        let exe = fake_exe("exe_arg");
+        let exe_str = exe.to_string_lossy().to_string();
+        let command = format!("{} --target other.exe", exe_str);
+        assert_eq!(
+            get_executable_and_args_from_command(&command),
+            Some((exe_str, vec!["--target".to_string(), "other.exe".to_string()]))
+        );
+    }
+
+    #[test]
+    fn quoted_path_with_spaces_and_args() {
+        let exe = fake_exe("quoted");
+        let exe_str = exe.to_string_lossy().to_string();
+        let command = format!("\"{}\" --minimized", exe_str);
+        assert_eq!(
+            get_executable_and_args_from_command(&command),
+            Some((exe_str, vec!["--minimized".to_string()]))
+        );
+    }
+
+    #[test]
+    fn windows_paths_in_args_keep_their_backslashes() {
+        let exe = fake_exe("backslash_args");
+        let exe_str = exe.to_string_lossy().to_string();
+        let command = format!("{} --config C:\\Tools\\raid.ini \"D:\\Raid Logs\\\"", exe_str);
+        let (_, args) = get_executable_and_args_from_command(&command).unwrap();
+        assert_eq!(args, vec!["--config", "C:\\Tools\\raid.ini", "D:\\Raid Logs\\"]);
+        assert_eq!(split_arguments(&join_arguments(&args)), args);
+    }
+
+    #[test]
+    fn keybinds_normalize_modifier_order_and_case() {
+        assert_eq!(normalize_keybind("shift + alt+1"), "ALT+SHIFT+1");
+        assert_eq!(normalize_keybind("ALT+SHIFT+1"), "ALT+SHIFT+1");
+        assert_eq!(normalize_keybind("  "), "");
+    }
+
+    #[test]
+    fn precheck_address_uses_scheme_default_ports() {
+        assert_eq!(precheck_address("localhost:8080"), Some(("localhost".to_string(), 8080)));
+        assert_eq!(precheck_address("http://127.0.0.1/status"), Some(("127.0.0.1".to_string(), 80)));
+        assert_eq!(precheck_address("https://user@example.com/"), Some(("example.com".to_string(), 443)));
+        assert_eq!(precheck_address("http://[::1]:3000/"), Some(("::1".to_string(), 3000)));
+        assert_eq!(precheck_address("localhost"), None);
+    }
+
+    #[test]
+    fn urls_are_detected_but_drive_paths_are_not() {
+        assert!(is_url("steam://rungameid/1284210"));
+        assert!(is_url("https://wiki.guildwars2.com"));
+        assert!(!is_url("C:\\Tools\\app.exe"));
+        assert!(!is_url("C:\\Program Files\\app.exe --url https://example.com"));
+    }
+
+    #[test]
+    fn env_vars_are_expanded_and_unknown_ones_kept() {
+        std::env::set_var("ADD_TEST_TOOLS", "C:\\Tools");
+        assert_eq!(expand_env_vars("%ADD_TEST_TOOLS%\\app.exe"), "C:\\Tools\\app.exe");
+        assert_eq!(
+            expand_env_vars("%ADD_TEST_MISSING%\\%ADD_TEST_TOOLS%"),
+            "%ADD_TEST_MISSING%\\C:\\Tools"
+        );
+        assert_eq!(expand_env_vars("100% done"), "100% done");
+    }
+
+    #[test]
+    fn program_name_from_unquoted_path_with_spaces() {
+        let exe = fake_exe("name");
+        let command = format!("{} -x", exe.to_string_lossy());
+        assert_eq!(get_program_name_from_command(&command), Some("My App.exe".to_string()));
+    }
+
+    #[test]
+    fn batch_scripts_keep_quoted_arguments() {
+        let args = split_arguments(r#"--name "My Build" a&b"#);
+        assert_eq!(
+            batch_command_line(r"C:\My Scripts\setup.bat", &args),
+            r#"/d /s /c ""C:\My Scripts\setup.bat" --name "My Build" "a&b"""#
+        );
+        assert!(is_batch_script(r"C:\Tools\run.CMD"));
+        assert!(!is_batch_script(r"C:\Tools\run.exe"));
+    }
+
+    #[test]
+    fn powershell_scripts_receive_their_arguments() {
+        let interpreter = split_arguments(&powershell_command(PowerShellHost::Pwsh, "RemoteSigned"));
+        let args = split_arguments(r#"-Mode "Full Sync" -Verbose"#);
+        let (exe, invocation) = script_invocation(&interpreter, r"C:\My Scripts\sync.ps1", &args);
+        assert_eq!(exe, "pwsh.exe");
+        assert_eq!(
+            invocation,
+            vec![
+                "-NoProfile",
+                "-ExecutionPolicy",
+                "RemoteSigned",
+                "-File",
+                r"C:\My Scripts\sync.ps1",
+                "-Mode",
+                "Full Sync",
+                "-Verbose",
+            ]
+        );
+        assert_eq!(
+            powershell_command(PowerShellHost::WindowsPowerShell, ""),
+            "powershell.exe -NoProfile -File"
+        );
+    }
+
+    #[test]
+    fn affinity_mask_is_clamped_to_available_cores() {
+        assert_eq!(clamp_affinity_mask(0b1111_0000, 8), Some(0b1111_0000));
+        assert_eq!(clamp_affinity_mask(0b1_0000_0011, 8), Some(0b11));
+        assert_eq!(clamp_affinity_mask(0b1_0000_0000, 8), None);
+        assert_eq!(clamp_affinity_mask(u64::MAX, 64), Some(u64::MAX));
+    }
+
+    fn sample_profiles() -> ProfileStore {
+        let program = ProgramToLaunch {
+            name: "Blish_HUD".to_string(),
+            display_name: "Blish HUD".to_string(),
+            path: "C:\\Games\\Blish HUD\\Blish HUD.exe".to_string(),
+            triggers: BTreeSet::from([LaunchTrigger::OnAddonLoad, LaunchTrigger::OnMapEnter(1206)]),
+            env: HashMap::from([("MODE".to_string(), "overlay".to_string())]),
+            cpu_affinity: Some(u64::MAX),
+            ..Default::default()
+        };
+        let config = Config {
+            programs_to_launch: vec![program],
+            programs_to_kill: vec!["Blish HUD.exe".to_string()],
+            ..Default::default()
+        };
+        ProfileStore {
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profiles: HashMap::from([(DEFAULT_PROFILE.to_string(), config)]),
+        }
+    }
+
+    #[test]
+    fn profiles_round_trip_in_both_formats() {
+        let store = sample_profiles();
+        for (format, _) in CONFIG_FORMATS {
+            let serialized = serialize_profiles(&store, format).unwrap();
+            let parsed = parse_profiles(&serialized, format).unwrap();
+            assert_eq!(parsed.active_profile, DEFAULT_PROFILE);
+            let config = &parsed.profiles[DEFAULT_PROFILE];
+            assert_eq!(config.version, CONFIG_VERSION);
+            assert_eq!(config.programs_to_kill, vec!["Blish HUD.exe".to_string()]);
+            let program = &config.programs_to_launch[0];
+            assert_eq!(program.display_name, "Blish HUD");
+            assert!(program.triggers.contains(&LaunchTrigger::OnMapEnter(1206)));
+            assert_eq!(program.env["MODE"], "overlay");
+            assert_eq!(program.cpu_affinity, Some(u64::MAX));
+        }
+    }
+
+    #[test]
+    fn json_profiles_are_plain_json() {
+        let serialized = serialize_profiles(&sample_profiles(), ConfigFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(value["active_profile"], DEFAULT_PROFILE);
+        assert_eq!(value["profiles"][DEFAULT_PROFILE]["programs_to_launch"][0]["name"], "Blish_HUD");
+    }
+
+    #[test]
+    fn program_codes_round_trip_and_reject_garbage() {
+        let exe = fake_exe("program_code");
+        let program = ProgramToLaunch {
+            name: "My_App".to_string(),
+            display_name: "My App".to_string(),
+            path: exe.to_string_lossy().to_string(),
+            launch_count: 12,
+            ..Default::default()
+        };
+        let decoded = decode_program_code(&encode_program_code(&program).unwrap()).unwrap();
+        assert_eq!(decoded.display_name, "My App");
+        assert_eq!(decoded.path, program.path);
+        assert_eq!(decoded.launch_count, 0);
+
+        assert!(decode_program_code("not base64!").is_err());
+        assert!(decode_program_code(&BASE64.encode("(name: 1)")).is_err());
+    }
+
+    #[test]
+    fn favorites_move_up_without_reordering_the_rest() {
+        let mut programs: Vec<ProgramToLaunch> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| ProgramToLaunch {
+                name: name.to_string(),
+                is_favorite: *name == "c" || *name == "d",
+                ..Default::default()
+            })
+            .collect();
+        favorites_first(&mut programs);
+        let order: Vec<&str> = programs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(order, ["c", "d", "a", "b"]);
+    }
+
+    #[test]
+    fn memory_limit_needs_to_drop_clearly_before_warning_again() {
+        assert!(!memory_over_limit(false, 1000, 1000));
+        assert!(memory_over_limit(false, 1001, 1000));
+        assert!(memory_over_limit(true, 950, 1000));
+        assert!(!memory_over_limit(true, 899, 1000));
+    }
+
+    #[test]
+    fn combined_commands_are_split_only_when_upgrading_to_format_3() {
+        let exe = fake_exe("upgrade");
+        let exe_str = exe.to_string_lossy().to_string();
+        let program = ProgramToLaunch {
+            path: format!("{} --minimized", exe_str),
+            ..Default::default()
+        };
+        let mut old = Config {
+            version: 2,
+            programs_to_launch: vec![program.clone()],
+            ..Default::default()
+        };
+        assert!(upgrade_config(&mut old));
+        assert_eq!(old.programs_to_launch[0].exe_path, exe_str);
+        assert_eq!(old.programs_to_launch[0].args, vec!["--minimized".to_string()]);
+
+        let mut current = Config {
+            programs_to_launch: vec![program],
+            ..Default::default()
+        };
+        assert!(!upgrade_config(&mut current));
+        assert!(current.programs_to_launch[0].exe_path.is_empty());
+    }
+}